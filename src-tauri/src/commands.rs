@@ -1,10 +1,183 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
-use std::process::{Command, Child};
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, State};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Error prefix for files that were moved or deleted since they were downloaded
+const FILE_NOT_FOUND_ERROR: &str = "FileNotFound";
+
+// Error prefixes for videos that need a channel membership or fresh cookies (see ErrorCategory)
+const MEMBERS_ONLY_ERROR: &str = "MembersOnly";
+const COOKIES_EXPIRED_ERROR: &str = "CookiesExpired";
+
+// Error prefix for requests YouTube refused with HTTP 429 (see ErrorCategory)
+const RATE_LIMITED_ERROR: &str = "RateLimited";
+
+// Error prefix for downloads skipped because they exceed max_filesize
+const FILE_TOO_LARGE_ERROR: &str = "FileTooLarge";
+
+// Quality presets accepted in DownloadOptions.quality, highest first
+const QUALITY_PRESETS: [&str; 7] = ["best", "2160p", "1440p", "1080p", "720p", "480p", "360p"];
+
+// Format selection for audio_only downloads; falls back to a combined stream to extract from
+const AUDIO_ONLY_FORMAT: &str = "bestaudio/best";
+
+// Audio formats accepted by yt-dlp's --audio-format
+const AUDIO_FORMATS: [&str; 8] = ["best", "mp3", "m4a", "aac", "opus", "vorbis", "flac", "wav"];
+
+// Common subtitle languages (code, English name) for the UI's language picker
+const COMMON_SUBTITLE_LANGUAGES: [(&str, &str); 30] = [
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("sv", "Swedish"),
+    ("no", "Norwegian"),
+    ("da", "Danish"),
+    ("fi", "Finnish"),
+    ("pl", "Polish"),
+    ("cs", "Czech"),
+    ("hu", "Hungarian"),
+    ("ro", "Romanian"),
+    ("el", "Greek"),
+    ("tr", "Turkish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("ar", "Arabic"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("bn", "Bengali"),
+    ("th", "Thai"),
+    ("vi", "Vietnamese"),
+    ("id", "Indonesian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh-Hans", "Chinese (Simplified)"),
+    ("zh-Hant", "Chinese (Traditional)"),
+];
+
+// Fields accepted by yt-dlp's -S/--format-sort
+const FORMAT_SORT_FIELDS: [&str; 28] = [
+    "hasvid", "hasaud", "ie_pref", "lang", "quality", "source", "proto", "vcodec", "acodec",
+    "codec", "vext", "aext", "ext", "filesize", "fs_approx", "size", "height", "width", "res",
+    "fps", "hdr", "channels", "tbr", "vbr", "abr", "br", "asr", "id",
+];
+
+// Largest thumbnail fetch_thumbnail_base64 will download
+const THUMBNAIL_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+// YouTube thumbnail sizes from smallest to largest, with their file names
+const THUMBNAIL_QUALITIES: [(&str, &str); 5] = [
+    ("default", "default"),
+    ("medium", "mqdefault"),
+    ("high", "hqdefault"),
+    ("standard", "sddefault"),
+    ("maxres", "maxresdefault"),
+];
+
+// Prefix of the structured progress lines produced by PROGRESS_TEMPLATE
+const PROGRESS_PREFIX: &str = "[progress] ";
+
+// yt-dlp --progress-template printing yt-dlp's progress dict as one line of JSON
+// (see ProgressTemplateLine), so fragment downloads and localized output parse the same way
+const PROGRESS_TEMPLATE: &str = "download:[progress] %(progress)j";
+
+// First yt-dlp release with --progress-template and the JSON ("j") conversion
+const PROGRESS_TEMPLATE_MIN_VERSION: &str = "2022.01.21";
+
+// Oldest yt-dlp known to still extract YouTube reliably with this app
+const RECOMMENDED_YT_DLP_VERSION: &str = "2025.10.22";
+
+// How long the latest-release lookup on GitHub is cached
+const YT_DLP_RELEASE_CACHE_SECS: u64 = 24 * 60 * 60;
+
+// How often download-heartbeat is emitted while a download runs
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Number of trailing yt-dlp stderr lines kept on a failed queue entry
+const FAILED_STDERR_LINES: usize = 20;
+
+// Number of recent speed samples averaged for the smoothed ETA
+const ETA_SMOOTHING_WINDOW: usize = 10;
+
+// Lowercase fragments of yt-dlp warnings about signature/nsig extraction failures
+const SIGNATURE_WARNING_PATTERNS: [&str; 4] = [
+    "nsig extraction failed",
+    "signature extraction failed",
+    "some formats may be missing",
+    "unable to extract nsig",
+];
+
+// YouTube player clients tried by find_working_client
+const PLAYER_CLIENTS: [&str; 4] = ["web", "android", "ios", "tv"];
+
+// Speed below which a download counts as throttled when no throttled_rate is given
+const DEFAULT_THROTTLE_THRESHOLD_BPS: f64 = 100.0 * 1024.0;
+
+// How often a running download's progress is saved to queue.json
+const QUEUE_PROGRESS_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+// How long the speed must stay below the threshold before download-throttled is emitted
+const THROTTLE_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Default format: best mp4 video+audio, merged by yt-dlp
+const DEFAULT_FORMAT: &str = "bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best";
+
+// Built-in -o template for downloads without an output path; relative templates
+// are placed in the save location
+const DEFAULT_OUTPUT_TEMPLATE: &str = "%(title)s.%(ext)s";
+
+// Longest full path the platform accepts
+#[cfg(target_os = "windows")]
+const MAX_PATH_LENGTH: usize = 260;
+#[cfg(target_os = "macos")]
+const MAX_PATH_LENGTH: usize = 1024;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const MAX_PATH_LENGTH: usize = 4096;
+
+// Room left in a path for what --trim-filenames doesn't count: the extension plus
+// yt-dlp's temporary suffixes such as ".f137.mp4.part"
+const FILENAME_SUFFIX_ALLOWANCE: usize = 20;
+
+// Fields accepted in saved output templates. yt-dlp renders unknown fields as "NA"
+// instead of failing, so typos are caught here.
+const OUTPUT_TEMPLATE_FIELDS: &[&str] = &[
+    "id", "title", "fulltitle", "alt_title", "ext", "description", "display_id",
+    "uploader", "uploader_id", "uploader_url", "creator", "creators", "license",
+    "timestamp", "upload_date", "release_date", "release_timestamp", "release_year",
+    "modified_date", "modified_timestamp", "epoch", "autonumber", "video_autonumber",
+    "channel", "channel_id", "channel_url", "channel_follower_count", "channel_is_verified",
+    "location", "duration", "duration_string", "view_count", "concurrent_view_count",
+    "like_count", "dislike_count", "repost_count", "average_rating", "comment_count",
+    "age_limit", "live_status", "is_live", "was_live", "availability", "media_type",
+    "extractor", "extractor_key", "webpage_url", "webpage_url_basename", "webpage_url_domain",
+    "original_url", "categories", "tags", "cast", "language",
+    "playlist", "playlist_id", "playlist_title", "playlist_count", "playlist_index",
+    "playlist_autonumber", "playlist_uploader", "playlist_uploader_id", "playlist_channel",
+    "playlist_channel_id", "playlist_webpage_url", "n_entries",
+    "chapter", "chapter_number", "chapter_id", "section_title", "section_number",
+    "section_start", "section_end", "start_time", "end_time",
+    "series", "series_id", "season", "season_number", "season_id",
+    "episode", "episode_number", "episode_id",
+    "track", "track_number", "track_id", "artist", "artists", "genre", "genres",
+    "album", "album_type", "album_artist", "album_artists", "disc_number",
+    "composer", "composers",
+    "format", "format_id", "format_note", "width", "height", "aspect_ratio", "resolution",
+    "dynamic_range", "fps", "vcodec", "vbr", "acodec", "abr", "asr", "audio_channels",
+    "tbr", "container", "protocol", "filesize", "filesize_approx",
+];
+
+// Default for yt-dlp's --retries and --fragment-retries
+const DEFAULT_RETRIES: &str = "10";
+
+// Best streams regardless of codec or container, used with prefer_quality_over_compatibility
+const BEST_QUALITY_FORMAT: &str = "bestvideo+bestaudio/best";
 
 // Type definitions
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -23,6 +196,8 @@ pub struct VideoInfo {
     pub duration: u64,
     pub uploader: String,
     pub thumbnail: String,
+    #[serde(default)]
+    pub channel_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -34,6 +209,10 @@ pub struct VideoFormat {
     pub filesize: Option<u64>,
     pub vcodec: String,
     pub acodec: String,
+    #[serde(default)]
+    pub is_video_only: bool,
+    #[serde(default)]
+    pub is_audio_only: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,16 +220,175 @@ pub struct Subtitle {
     pub lang: String,
     pub name: String,
     pub format: String,
+    // True for YouTube's auto-generated captions
+    #[serde(default)]
+    pub is_auto: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Whether the subtitle for subtitle_translate_to is an original or a machine-translated track
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleSource {
+    Original,
+    Translated,
+}
+
+// Which subtitle tracks to download
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleMode {
+    #[default]
+    Manual,
+    Auto,
+    Both,
+}
+
+// What a download does when its file name is already taken
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FilenameCollision {
+    // Replace the existing file
+    Overwrite,
+    // Save as "name (1).ext", "name (2).ext", ...
+    // (accepts the earlier "autonumber" spelling so saved settings still load)
+    #[serde(alias = "autonumber")]
+    AutoNumber,
+    // Leave the existing file and don't download
+    #[default]
+    Skip,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct DownloadOptions {
     pub url: String,
+    // Format ID picked from get_available_formats (e.g. "137" or "137+140"). A container
+    // name such as "mp4", or nothing, leaves the choice to the quality settings.
     pub format: String,
+    // Download `format` exactly as given: one stream with no fallback, added audio or merge
+    // (see download_specific_format)
+    #[serde(default)]
+    pub exact_format: bool,
     pub output: String,
     pub subtitles: bool,
     pub subtitle_langs: Option<Vec<String>>,
+    // Language to get subtitles in even when the video has none in it; falls back to
+    // YouTube's machine translation of the auto-generated captions
+    pub subtitle_translate_to: Option<String>,
+    // Download YouTube's auto-generated captions too (true) or only manual subtitles
+    // (false); unset follows the subtitle mode in settings
+    pub auto_subtitles: Option<bool>,
+    // Mux the downloaded subtitle tracks into the mp4/mkv instead of leaving .srt sidecars
+    // (the sidecars are kept when burn_subtitles needs them)
+    #[serde(default)]
+    pub embed_subtitles: bool,
     pub cookies: Option<String>,
+    // Quality preset such as "1080p" or "best" (see QUALITY_PRESETS)
+    pub quality: Option<String>,
+    // Split the finished file into equal parts no longer than this many seconds
+    pub max_duration_split: Option<u64>,
+    // Netscape-format cookies pasted by the user, used instead of a cookies file
+    pub cookies_content: Option<String>,
+    // Title shown while downloading; defaults to the output file name
+    pub title: Option<String>,
+    // Extract audio in this format (mp3, m4a, opus, flac, ...) after downloading
+    pub audio_format: Option<String>,
+    // With audio_format, keep the video file as well as the extracted audio
+    #[serde(default)]
+    pub keep_video_and_audio: bool,
+    // Download only the best audio stream and extract it (in audio_format, default "best"),
+    // so no video is downloaded at all
+    #[serde(default)]
+    pub audio_only: bool,
+    // yt-dlp --audio-quality for extracted audio: a bitrate such as "192K", or a VBR
+    // quality from 0 (best) to 10 (worst)
+    pub audio_quality: Option<String>,
+    // yt-dlp --match-filter expressions (e.g. "duration > 60", "!is_live"), all of
+    // which must match; only applied to playlist and channel URLs
+    pub match_filters: Option<Vec<String>>,
+    // yt-dlp -S sort criteria (e.g. "res,fps,vcodec:av1") deciding which format is "best".
+    // Sorting applies within the quality preset's constraint, e.g. among formats <= 1080p.
+    pub format_sort: Option<String>,
+    // Open the downloaded file with the default app once the download succeeds
+    #[serde(default)]
+    pub open_on_complete: bool,
+    // Also produce a "-hardsub" copy of the video with subtitles burned in (re-encodes)
+    #[serde(default)]
+    pub burn_subtitles: bool,
+    // Subtitle language to burn in; defaults to the first configured subtitle language
+    pub burn_subtitle_lang: Option<String>,
+    // Passed as --throttled-rate (e.g. "100K") so yt-dlp re-extracts when the speed drops below it
+    pub throttled_rate: Option<String>,
+    // Save the thumbnail as an image next to the video
+    #[serde(default)]
+    pub write_thumbnail: bool,
+    // Embed the thumbnail as cover art (yt-dlp picks the largest, i.e. maxres when available)
+    #[serde(default)]
+    pub embed_thumbnail: bool,
+    // Write the title, uploader, date, description etc. into the file's tags
+    #[serde(default)]
+    pub embed_metadata: bool,
+    // Add the video's chapters as chapter markers in the file
+    #[serde(default)]
+    pub embed_chapters: bool,
+    // Thumbnail size to save with write_thumbnail (see THUMBNAIL_QUALITIES); falls back to
+    // the next smaller size when the video doesn't have it. Only affects the saved image:
+    // embed_thumbnail always embeds yt-dlp's largest thumbnail.
+    pub thumbnail_quality: Option<String>,
+    // For playlists, skip items already downloaded by earlier runs (yt-dlp --download-archive)
+    #[serde(default)]
+    pub download_archive: bool,
+    // Skip formats larger than this (e.g. "500M"), passed as --max-filesize
+    pub max_filesize: Option<String>,
+    // --extractor-args for this download, replacing the default from settings
+    pub extractor_args: Option<Vec<String>>,
+    // Add the download time to the file name so repeat downloads never collide
+    #[serde(default)]
+    pub dedup_by_timestamp: bool,
+    // Channel of the video (VideoInfo.channel_id); when set, the player client that
+    // find_working_client found for this channel is used unless extractor_args is given
+    pub channel_id: Option<String>,
+    // Take the best video and audio in any codec (VP9/AV1/Opus often only exist as webm)
+    // and merge them into mkv. Higher quality than the default mp4/m4a selection, but the
+    // result may not play in QuickTime, older TVs, or editors that expect H.264/AAC mp4.
+    // Needs ffmpeg for the merge.
+    #[serde(default)]
+    pub prefer_quality_over_compatibility: bool,
+    // When the quality preset still fails after yt-dlp's own retries, try again with
+    // each lower preset (e.g. 1080p -> 720p -> 480p) instead of failing the download.
+    // Single videos only; a playlist would be downloaded again from the start.
+    #[serde(default)]
+    pub quality_fallback: bool,
+    // --user-agent and --referer for this download, replacing the defaults from settings
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+    // 1-based range of playlist items to download (--playlist-start/--playlist-end);
+    // either end may be left open
+    pub playlist_start: Option<u32>,
+    pub playlist_end: Option<u32>,
+    // yt-dlp's own retries: --fragment-retries for individual fragments and --retries for
+    // the whole file. A number or "infinite"; both default to DEFAULT_RETRIES.
+    pub fragment_retries: Option<String>,
+    pub retries: Option<String>,
+    // Only download this part of the video, passed as --download-sections (e.g. "*90-245.5")
+    pub download_section: Option<String>,
+    // Clip to a time range, as seconds or [[hh:]mm:]ss (e.g. "90", "1:30", "1:02:03.5").
+    // Either end may be left open. Cuts are re-encoded at keyframes so the clip starts cleanly.
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    // Also split the video into one file per chapter, named "<title> - 001 <chapter>.<ext>"
+    // next to the video. The chapter files are listed after it in download-complete.
+    #[serde(default)]
+    pub split_chapters: bool,
+}
+
+impl DownloadOptions {
+    // Copy that is safe to write to disk: pasted cookies are never persisted
+    fn persistable(&self) -> DownloadOptions {
+        DownloadOptions {
+            cookies_content: None,
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,8 +397,58 @@ pub struct DownloadProgress {
     pub progress: f64,
     pub speed: String,
     pub eta: String,
+    // Human-readable sizes, e.g. "12.50MiB"
     pub downloaded: String,
     pub total_size: String,
+    pub downloaded_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    pub speed_bps: Option<f64>,
+    pub fragment_index: Option<u64>,
+    pub fragment_count: Option<u64>,
+    pub phase: DownloadPhase,
+}
+
+// What a download is doing, from yt-dlp's output
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadPhase {
+    Starting,
+    // A single file, or a stream that can't be told apart as video or audio
+    Downloading,
+    DownloadingVideo,
+    DownloadingAudio,
+    Merging,
+    ExtractingAudio,
+    PostProcessing,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistProgress {
+    pub id: String,
+    pub completed: u32,
+    pub total: u32,
+    pub current_title: String,
+    pub estimated_remaining_secs: Option<u64>,
+}
+
+// Why a playlist item was not downloaded
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistSkipReason {
+    Private,
+    Deleted,
+    Failed,
+}
+
+// Item counts reported when a playlist download finishes
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlaylistSummary {
+    pub downloaded: u32,
+    pub skipped_private: u32,
+    pub skipped_deleted: u32,
+    pub failed: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -76,34 +464,251 @@ pub struct RecentDownload {
     pub quality: String,
     pub downloaded_at: String,
     pub format: String,
+    // Other files produced by the same download (e.g. extracted audio)
+    #[serde(default)]
+    pub extra_files: Vec<String>,
+    #[serde(default)]
+    pub uploader: String,
+    // What ffprobe found in the saved file, if it could be probed
+    #[serde(default)]
+    pub probe: Option<MediaProbe>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Missing fields default so credentials saved by older versions still load
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct Credentials {
+    #[serde(alias = "accessToken")]
     pub access_token: String,
+    #[serde(alias = "refreshToken")]
     pub refresh_token: String,
     pub cookies: String,
+    // Unix timestamp at which the access token expires (0 if unknown)
+    pub expires: u64,
+}
+
+// App settings persisted in ~/.youtube-downloader/settings.json
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    // Authentication method for yt-dlp: "cookies" or "oauth"
+    pub auth_method: String,
+    // Enables diagnostic commands such as get_raw_video_json
+    pub debug_mode: bool,
+    // Quality preset used when DownloadOptions.quality is not set
+    pub default_quality: Option<String>,
+    pub subtitle_mode: SubtitleMode,
+    // Command run after each download, e.g. `rsync {filepath} nas:/videos/`.
+    // Supports {filepath}, {title}, and {url}; executed without a shell.
+    pub post_download_command: Option<String>,
+    pub post_download_command_enabled: bool,
+    // Subtitle languages to download; defaults to the system language
+    pub subtitle_langs: Vec<String>,
+    // Maximum number of recent downloads kept in history (0 = unlimited)
+    pub max_history_items: usize,
+    // Total download bandwidth in bytes per second, shared across concurrent downloads
+    pub global_rate_limit: Option<u64>,
+    // Seconds without any yt-dlp output before a download is reported as stalled
+    pub stall_timeout_secs: u64,
+    // Default --extractor-args for every yt-dlp call, e.g. "youtube:player_client=android"
+    pub extractor_args: Vec<String>,
+    // User's own yt-dlp -f chain, used when a download doesn't ask for a quality preset
+    pub format_preference: Option<String>,
+    // Periodically re-export cookies from cookie_browser while the app is open
+    pub enable_auto_cookie_refresh: bool,
+    pub cookie_refresh_interval_hours: u64,
+    // Browser to read cookies from; defaults to Chrome
+    pub cookie_browser: Option<String>,
+    // Default --user-agent and --referer for every yt-dlp call (works around some 403s)
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+    // Limits on info fetches (yt-dlp metadata calls) so bursts don't get the IP soft-banned:
+    // at most this many at once, started at least info_fetch_interval_ms apart
+    pub max_concurrent_info_fetches: usize,
+    pub info_fetch_interval_ms: u64,
+    // -o template for downloads that don't give an output path (None = DEFAULT_OUTPUT_TEMPLATE)
+    pub output_template: Option<String>,
+    // Downloads running at once before queued ones wait; downloads started directly
+    // with start_download take a slot too
+    pub max_concurrent_downloads: usize,
+    // Minutes between checks of subscribed channels and playlists for new uploads
+    pub subscription_check_interval_mins: u64,
+    // Automatic retries of a queued download after a transient failure (HTTP 403/429,
+    // dropped connections); the nth retry waits download_retry_backoff_secs * 2^(n-1)
+    pub download_retry_attempts: u32,
+    pub download_retry_backoff_secs: u64,
+    // Watch the clipboard for copied video links and emit url-detected for them. With
+    // clipboard_auto_download they are queued right away instead of waiting for confirmation.
+    pub clipboard_monitoring: bool,
+    pub clipboard_auto_download: bool,
+    // Replace characters that aren't allowed in file names on Windows or macOS
+    // (passes --windows-filenames), so downloads can be copied between systems
+    pub portable_filenames: bool,
+    // Longest file name (without extension) yt-dlp may write; 0 = only the platform's
+    // path length limit applies
+    pub max_filename_length: usize,
+    pub filename_collision: FilenameCollision,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            auth_method: "cookies".to_string(),
+            debug_mode: false,
+            default_quality: None,
+            subtitle_mode: SubtitleMode::Manual,
+            post_download_command: None,
+            post_download_command_enabled: false,
+            subtitle_langs: vec![system_language()],
+            max_history_items: 100,
+            global_rate_limit: None,
+            stall_timeout_secs: 60,
+            extractor_args: Vec::new(),
+            format_preference: None,
+            enable_auto_cookie_refresh: false,
+            cookie_refresh_interval_hours: 12,
+            cookie_browser: None,
+            user_agent: None,
+            referer: None,
+            max_concurrent_info_fetches: 4,
+            info_fetch_interval_ms: 200,
+            output_template: None,
+            max_concurrent_downloads: 3,
+            subscription_check_interval_mins: 60,
+            download_retry_attempts: 3,
+            download_retry_backoff_secs: 10,
+            clipboard_monitoring: false,
+            clipboard_auto_download: false,
+            portable_filenames: true,
+            max_filename_length: 200,
+            filename_collision: FilenameCollision::Skip,
+        }
+    }
+}
+
+// Lifecycle state of a download tracked by DownloadManager
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    Queued,
+    Running,
+    Paused,
+}
+
+// A download currently tracked by DownloadManager
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveDownload {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub progress: f64,
+    pub state: DownloadState,
+    // PID of the yt-dlp process, which leads its own process group on Unix so signals
+    // also reach the ffmpeg processes it starts
+    #[serde(skip)]
+    pid: Option<u32>,
+    // The .part file yt-dlp is currently writing to
+    #[serde(skip)]
+    part_path: Option<String>,
+    // Every file yt-dlp has written to, for cleaning up temp files on cancel
+    #[serde(skip)]
+    destinations: Vec<String>,
+}
+
+// A download stopped by cancel_download with keep_partial, resumable via resume_download
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoppedDownload {
+    pub id: String,
+    pub options: DownloadOptions,
+    // The kept partial file, renamed from .part to .partial
+    pub partial_path: String,
+}
+
+// State of an entry in the download queue
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueState {
+    Pending,
+    Active,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+// A download added with enqueue_download. The ID is also the download ID used in
+// download events once the entry starts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueEntry {
+    pub id: String,
+    pub options: DownloadOptions,
+    pub state: QueueState,
+    pub error: Option<String>,
+    // Last lines yt-dlp wrote to stderr before the download failed
+    pub stderr: Option<String>,
+    // Automatic retries made after transient failures
+    pub retries: u32,
+    // Unix timestamp (seconds) before which a pending retry isn't started
+    pub retry_at: Option<u64>,
+}
+
+// A download waiting for its start time, saved to scheduled.json
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledDownload {
+    pub id: String,
+    pub options: DownloadOptions,
+    // Unix timestamp (seconds) at which the download is queued
+    pub start_time: u64,
+}
+
+// A download saved to queue.json so it survives an app restart or crash
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersistedDownload {
+    pub id: String,
+    pub options: DownloadOptions,
+    // Queued entries were waiting to start; running ones were interrupted mid-download
+    pub state: DownloadState,
+    // Last known progress percentage, saved every QUEUE_PROGRESS_SAVE_INTERVAL while running
+    #[serde(default)]
+    pub progress: f64,
 }
 
 // Global state for tracking downloads
 pub struct DownloadManager {
-    downloads: Mutex<HashMap<String, Child>>,
+    playlist_progress: Mutex<HashMap<String, PlaylistProgress>>,
+    // Latest progress per download ID; finished downloads keep their final state
+    progress: Mutex<HashMap<String, DownloadProgress>>,
+    // Downloads that are running or paused, keyed by download ID
+    active: Mutex<HashMap<String, ActiveDownload>>,
+    // Downloads added with enqueue_download, in the order they were added
+    queue: Mutex<Vec<QueueEntry>>,
+    // Set by pause_all_downloads so no new downloads are dispatched until resumed
+    queue_paused: Mutex<bool>,
+    // Active URL watch list; dropping the watcher stops watching
+    watch_list: Mutex<Option<notify::RecommendedWatcher>>,
+    // Set while the app exits so interrupted downloads stay in queue.json
+    shutting_down: Mutex<bool>,
 }
 
 impl DownloadManager {
     pub fn new() -> Self {
         Self {
-            downloads: Mutex::new(HashMap::new()),
+            playlist_progress: Mutex::new(HashMap::new()),
+            progress: Mutex::new(HashMap::new()),
+            active: Mutex::new(HashMap::new()),
+            queue: Mutex::new(Vec::new()),
+            queue_paused: Mutex::new(false),
+            watch_list: Mutex::new(None),
+            shutting_down: Mutex::new(false),
         }
     }
 }
 
-// Helper function to get recent downloads storage path
-fn get_recent_downloads_path() -> Result<PathBuf, String> {
+// Helper function to get the persisted download queue path
+fn get_queue_path() -> Result<PathBuf, String> {
     let mut path = dirs::home_dir()
         .ok_or("Failed to get home directory")?;
     path.push(".youtube-downloader");
-    path.push("recent-downloads.json");
+    path.push("queue.json");
     
     // Create directory if it doesn't exist
     if let Some(parent) = path.parent() {
@@ -113,607 +718,6155 @@ fn get_recent_downloads_path() -> Result<PathBuf, String> {
     Ok(path)
 }
 
-// Helper function to validate YouTube URL
-#[tauri::command]
-pub fn validate_url(url: String) -> Result<VideoMetadata, String> {
-    // Basic YouTube URL validation
-    let youtube_regex = regex::Regex::new(
-        r"^(https?://)?(www\.)?(youtube\.com/(watch\?v=|shorts/)|youtu\.be/)[\w-]+"
-    ).map_err(|e| format!("Failed to create regex: {}", e))?;
-
-    if !youtube_regex.is_match(&url) {
-        return Err("Invalid YouTube URL".to_string());
-    }
-
-    // For now, return a placeholder metadata
-    // In a real implementation, we would use yt-dlp to fetch actual metadata
-    Ok(VideoMetadata {
-        title: "Video Title".to_string(),
-        thumbnail: "".to_string(),
-        duration: "0:00".to_string(),
-        uploader: "Uploader".to_string(),
-    })
+// Helper function to read the persisted queue (empty if missing or invalid)
+fn read_persisted_queue() -> Vec<PersistedDownload> {
+    get_queue_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
 }
 
-// Helper struct to hold yt-dlp path and resources directory
-struct YtDlpInfo {
-    path: String,
-    resources_dir: Option<String>,
-    bun_path: Option<String>,
+// Helper function to apply a change to the persisted queue and write it back
+fn update_persisted_queue(update: impl FnOnce(&mut Vec<PersistedDownload>)) {
+    let mut queue = read_persisted_queue();
+    update(&mut queue);
+    for download in queue.iter_mut() {
+        download.options = download.options.persistable();
+    }
+    
+    let result = get_queue_path().and_then(|path| {
+        let json = serde_json::to_string_pretty(&queue)
+            .map_err(|e| format!("Failed to serialize queue: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write queue: {}", e))
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to persist download queue: {}", e);
+    }
 }
 
-// Helper function to find yt-dlp executable
-fn find_yt_dlp() -> Result<String, String> {
-    find_yt_dlp_with_resources().map(|info| info.path)
+// Save the frontend's pending (not yet started) downloads so they can be restored after a restart
+#[tauri::command]
+pub async fn save_pending_queue(items: Vec<DownloadOptions>, manager: State<'_, DownloadManager>) -> Result<(), String> {
+    // Entries waiting in the backend queue are persisted by enqueue_download
+    let backend_pending: HashSet<String> = manager.queue.lock().unwrap()
+        .iter()
+        .filter(|entry| entry.state == QueueState::Pending)
+        .map(|entry| entry.id.clone())
+        .collect();
+    update_persisted_queue(|queue| {
+        queue.retain(|d| d.state != DownloadState::Queued || backend_pending.contains(&d.id));
+        queue.extend(items.into_iter().map(|options| PersistedDownload {
+            id: uuid::Uuid::new_v4().to_string(),
+            options,
+            state: DownloadState::Queued,
+            progress: 0.0,
+        }));
+    });
+    Ok(())
 }
 
-// Helper function to find yt-dlp executable and resources directory
-fn find_yt_dlp_with_resources() -> Result<YtDlpInfo, String> {
-    // First, try to find the bundled yt-dlp in the Resources directory
-    // When running as a bundled app, the executable is in .app/Contents/MacOS/
-    // and resources are in .app/Contents/Resources/
-    if let Ok(exe_path) = std::env::current_exe() {
-        let exe_str = exe_path.to_string_lossy().to_string();
-        eprintln!("DEBUG: exe_path = {}", exe_str);
-        
-        if let Some(contents_dir) = exe_path.parent().and_then(|p| p.parent()) {
-            let contents_dir_str = contents_dir.to_string_lossy().to_string();
-            eprintln!("DEBUG: contents_dir = {}", contents_dir_str);
-            
-            // Try Resources/binaries/yt-dlp first (this is where Tauri bundles resources)
-            let binaries_path = contents_dir.join("Resources").join("binaries").join("yt-dlp");
-            let binaries_path_str = binaries_path.to_string_lossy().to_string();
-            eprintln!("DEBUG: checking binaries_path = {}, exists = {}", binaries_path_str, binaries_path.exists());
-            
-            if binaries_path.exists() {
-                eprintln!("DEBUG: Found yt-dlp at {}", binaries_path_str);
-                // Return the binaries directory so bun can be found there too
-                let binaries_dir = contents_dir.join("Resources").join("binaries").to_string_lossy().to_string();
-                
-                // Check for bundled bun
-                let bun_path = contents_dir.join("Resources").join("binaries").join("bun");
-                let bun_path_str = if bun_path.exists() {
-                    eprintln!("DEBUG: Found bundled bun at {}", bun_path.to_string_lossy());
-                    Some(bun_path.to_string_lossy().to_string())
-                } else {
-                    eprintln!("DEBUG: Bundled bun not found at {}", bun_path.to_string_lossy());
-                    None
-                };
-                
-                return Ok(YtDlpInfo { 
-                    path: binaries_path_str, 
-                    resources_dir: Some(binaries_dir),
-                    bun_path: bun_path_str,
-                });
-            }
-            
-            // Try Resources/yt-dlp as fallback
-            let resources_path = contents_dir.join("Resources").join("yt-dlp");
-            let resources_path_str = resources_path.to_string_lossy().to_string();
-            eprintln!("DEBUG: checking resources_path = {}, exists = {}", resources_path_str, resources_path.exists());
-            
-            if resources_path.exists() {
-                eprintln!("DEBUG: Found yt-dlp at {}", resources_path_str);
-                let res_dir = contents_dir.join("Resources").to_string_lossy().to_string();
-                
-                // Check for bun in Resources
-                let bun_path = contents_dir.join("Resources").join("bun");
-                let bun_path_str = if bun_path.exists() {
-                    Some(bun_path.to_string_lossy().to_string())
-                } else {
-                    None
-                };
-                
-                return Ok(YtDlpInfo { 
-                    path: resources_path_str, 
-                    resources_dir: Some(res_dir),
-                    bun_path: bun_path_str,
-                });
-            }
-        }
+// Reload downloads that were interrupted (still running) when the last session ended,
+// and emit queue-restored with only those; queued ones are already back in the download
+// queue (see restore_pending_downloads). With resume, they are queued again under their
+// old IDs and yt-dlp picks up their .part files via --continue. Otherwise they are
+// dropped from queue.json so they aren't offered again.
+#[tauri::command]
+pub async fn restore_queue(resume: bool, app: AppHandle) -> Result<Vec<PersistedDownload>, String> {
+    // Downloads started in this session are persisted as running too
+    let live: HashSet<String> = {
+        let manager = app.state::<DownloadManager>();
+        let mut live: HashSet<String> = manager.active.lock().unwrap().keys().cloned().collect();
+        live.extend(manager.queue.lock().unwrap().iter().map(|entry| entry.id.clone()));
+        live
+    };
+    let interrupted: Vec<PersistedDownload> = read_persisted_queue()
+        .into_iter()
+        .filter(|d| d.state == DownloadState::Running && !live.contains(&d.id))
+        .collect();
+    
+    if let Err(e) = app.emit("queue-restored", &interrupted) {
+        eprintln!("Failed to emit queue-restored: {}", e);
     }
-
-    // Fallback: try to find yt-dlp using 'which' command
-    eprintln!("DEBUG: Trying 'which' command");
-    let output = Command::new("which")
-        .arg("yt-dlp")
-        .output()
-        .map_err(|e| format!("Failed to run 'which' command: {}", e))?;
-
-    if output.status.success() {
-        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        eprintln!("DEBUG: 'which' found yt-dlp at {}", path);
-        if !path.is_empty() {
-            Ok(YtDlpInfo { path, resources_dir: None, bun_path: None })
-        } else {
-            Err("yt-dlp not found. Please install yt-dlp using: brew install yt-dlp".to_string())
+    
+    if resume {
+        for download in &interrupted {
+            add_queue_entry(&app, &download.id, download.options.clone(), download.progress);
         }
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("DEBUG: 'which' failed: {}", stderr);
-        Err("yt-dlp not found. Please install yt-dlp using: brew install yt-dlp".to_string())
+        let declined: HashSet<&str> = interrupted.iter().map(|d| d.id.as_str()).collect();
+        update_persisted_queue(|queue| queue.retain(|d| !declined.contains(d.id.as_str())));
     }
+    
+    Ok(interrupted)
 }
 
-// Helper function to configure command with bundled resources in PATH and JS runtime
-fn configure_command_env(cmd: &mut Command, yt_dlp_info: &YtDlpInfo) {
-    if let Some(res_dir) = &yt_dlp_info.resources_dir {
-        // Add the resources directory to PATH so yt-dlp can find bundled bun
-        let current_path = std::env::var("PATH").unwrap_or_default();
-        let new_path = format!("{}:{}", res_dir, current_path);
-        cmd.env("PATH", new_path);
-        eprintln!("DEBUG: Set PATH to include resources dir: {}", res_dir);
+// Put downloads that were waiting when the app last closed back in the download queue.
+// Interrupted ones wait for restore_queue, so the UI can offer to resume them.
+// Called from setup in main.rs.
+pub fn restore_pending_downloads(app: AppHandle) {
+    for download in read_persisted_queue().into_iter().filter(|d| d.state == DownloadState::Queued) {
+        add_queue_entry(&app, &download.id, download.options, download.progress);
     }
+}
+
+// Helper function to get the path of the per-channel working player client cache
+fn get_player_clients_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("player-clients.json");
     
-    // Explicitly tell yt-dlp where to find bun using --js-runtimes
-    // --no-js-runtimes clears the deno default so bun takes priority
-    if let Some(bun_path) = &yt_dlp_info.bun_path {
-        cmd.arg("--no-js-runtimes");
-        cmd.arg("--js-runtimes").arg(format!("bun:{}", bun_path));
-        eprintln!("DEBUG: Set --js-runtimes bun:{}", bun_path);
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
+    
+    Ok(path)
 }
 
-// Helper function to get cookies file path
-fn get_cookies_path() -> Result<String, String> {
+// Helper function to read the cached player client per channel ID (empty if missing or invalid)
+fn read_player_client_cache() -> HashMap<String, String> {
+    get_player_clients_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Helper function to get the path of the saved playback positions
+fn get_playback_positions_path() -> Result<PathBuf, String> {
     let mut path = dirs::home_dir()
         .ok_or("Failed to get home directory")?;
     path.push(".youtube-downloader");
-    path.push("cookies.txt");
+    path.push("playback-positions.json");
     
-    if path.exists() {
-        Ok(path.to_string_lossy().to_string())
-    } else {
-        Err("Cookies file not found. Please run: yt-dlp --cookies-from-browser chrome --cookies ~/.youtube-downloader/cookies.txt --skip-download \"https://www.youtube.com/watch?v=dQw4w9WgXcQ\"".to_string())
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
+    
+    Ok(path)
 }
 
-// Helper function to find ffmpeg executable
-fn find_ffmpeg() -> Option<String> {
-    // Common ffmpeg locations on macOS
+// Helper function to read playback positions (seconds) per file path, dropping
+// positions for files that no longer exist
+fn read_playback_positions() -> HashMap<String, f64> {
+    let mut positions: HashMap<String, f64> = get_playback_positions_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    
+    let count = positions.len();
+    positions.retain(|path, _| std::path::Path::new(path).exists());
+    if positions.len() != count {
+        if let Err(e) = write_playback_positions(&positions) {
+            eprintln!("Failed to prune playback positions: {}", e);
+        }
+    }
+    positions
+}
+
+// Helper function to write playback positions
+fn write_playback_positions(positions: &HashMap<String, f64>) -> Result<(), String> {
+    let path = get_playback_positions_path()?;
+    let json = serde_json::to_string_pretty(positions)
+        .map_err(|e| format!("Failed to serialize playback positions: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write playback positions: {}", e))
+}
+
+// Helper function to get recent downloads storage path
+fn get_recent_downloads_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("recent-downloads.json");
+    
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    Ok(path)
+}
+
+// Helper function to read stored history (empty if nothing has been saved yet)
+fn read_recent_downloads() -> Result<Vec<RecentDownload>, String> {
+    let path = get_recent_downloads_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read recent downloads: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse recent downloads: {}", e))
+}
+
+// Helper function to write stored history
+fn write_recent_downloads(downloads: &[RecentDownload]) -> Result<(), String> {
+    let path = get_recent_downloads_path()?;
+    let json = serde_json::to_string_pretty(downloads)
+        .map_err(|e| format!("Failed to serialize recent downloads: {}", e))?;
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write recent downloads: {}", e))
+}
+
+// Helper function to truncate stored history to the newest max_items entries (0 = unlimited)
+fn prune_recent_downloads(max_items: usize) -> Result<(), String> {
+    if max_items == 0 {
+        return Ok(());
+    }
+    
+    let mut downloads = read_recent_downloads()?;
+    if downloads.len() <= max_items {
+        return Ok(());
+    }
+    downloads.truncate(max_items);
+    write_recent_downloads(&downloads)
+}
+
+// Helper function to get settings storage path
+fn get_settings_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("settings.json");
+    
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    Ok(path)
+}
+
+// Helper function to read settings, falling back to defaults if missing or invalid
+fn read_settings() -> AppSettings {
+    let Ok(path) = get_settings_path() else {
+        return AppSettings::default();
+    };
+    
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// On first run, persist the default settings so locale-based choices stay stable.
+// Called from setup in main.rs.
+pub fn init_settings() {
+    match get_settings_path() {
+        Ok(path) if !path.exists() => {
+            if let Err(e) = write_settings(&AppSettings::default()) {
+                eprintln!("Failed to write default settings: {}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to write default settings: {}", e),
+    }
+}
+
+// Helper function to get the system locale as a BCP 47 tag (e.g. "de-DE")
+fn system_locale() -> String {
+    sys_locale::get_locale()
+        .or_else(|| std::env::var("LANG").ok())
+        .unwrap_or_else(|| "en-US".to_string())
+}
+
+// Helper function to get the system language as one of COMMON_SUBTITLE_LANGUAGES,
+// falling back to English for anything else
+// Example: "pt-BR" -> "pt", "de_DE.UTF-8" -> "de", "zh-TW" -> "zh-Hant"
+fn system_language() -> String {
+    let locale = system_locale().to_lowercase();
+    let mut parts = locale.split(['-', '_', '.']);
+    let language = parts.next().unwrap_or("");
+    let region = parts.next().unwrap_or("");
+    
+    let language = match (language, region) {
+        ("zh", "tw" | "hk" | "mo" | "hant") => "zh-Hant",
+        ("zh", _) => "zh-Hans",
+        // Norwegian Bokmål and Nynorsk share YouTube's Norwegian tracks
+        ("nb" | "nn", _) => "no",
+        (language, _) => language,
+    };
+    
+    if COMMON_SUBTITLE_LANGUAGES.iter().any(|(code, _)| *code == language) {
+        language.to_string()
+    } else {
+        "en".to_string()
+    }
+}
+
+// Get the system locale
+#[tauri::command]
+pub async fn get_system_locale() -> Result<String, String> {
+    Ok(system_locale())
+}
+
+// Helper function to write settings to disk
+fn write_settings(settings: &AppSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+// Get settings
+#[tauri::command]
+pub async fn get_settings() -> Result<AppSettings, String> {
+    Ok(read_settings())
+}
+
+// Save settings
+#[tauri::command]
+pub async fn save_settings(settings: AppSettings) -> Result<(), String> {
+    if settings.auth_method != "cookies" && settings.auth_method != "oauth" {
+        return Err(format!("Invalid auth method: {}", settings.auth_method));
+    }
+    if let Some(quality) = &settings.default_quality {
+        if !QUALITY_PRESETS.contains(&quality.as_str()) {
+            return Err(format!("Unknown quality preset: {}", quality));
+        }
+    }
+    for arg in &settings.extractor_args {
+        validate_extractor_arg(arg)?;
+    }
+    validate_http_header("User-Agent", settings.user_agent.as_deref())?;
+    validate_http_header("Referer", settings.referer.as_deref())?;
+    if settings.stall_timeout_secs == 0 {
+        return Err("Stall timeout must be at least one second".to_string());
+    }
+    if settings.max_concurrent_info_fetches == 0 {
+        return Err("At least one concurrent info fetch is required".to_string());
+    }
+    if settings.max_concurrent_downloads == 0 {
+        return Err("At least one concurrent download is required".to_string());
+    }
+    if settings.subscription_check_interval_mins == 0 {
+        return Err("Subscription check interval must be at least one minute".to_string());
+    }
+    if settings.download_retry_backoff_secs == 0 {
+        return Err("Retry backoff must be at least one second".to_string());
+    }
+    if settings.cookie_refresh_interval_hours == 0 {
+        return Err("Cookie refresh interval must be at least one hour".to_string());
+    }
+    if let Some(command) = &settings.post_download_command {
+        if split_command_line(command)?.is_empty() {
+            return Err("Post-download command is empty".to_string());
+        }
+    }
+    
+    write_settings(&settings)?;
+    
+    // Lowering the limit drops the oldest entries right away
+    prune_recent_downloads(settings.max_history_items)
+}
+
+// Set the maximum number of recent downloads to keep (0 = unlimited)
+#[tauri::command]
+pub async fn set_max_history(max_items: usize) -> Result<(), String> {
+    let mut settings = read_settings();
+    settings.max_history_items = max_items;
+    write_settings(&settings)?;
+    
+    prune_recent_downloads(max_items)
+}
+
+// Helper function to parse a yt-dlp style size such as "500K", "2.5M", or "1048576" into bytes.
+// Also used for rates, which yt-dlp writes the same way (bytes per second).
+fn parse_byte_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let (number, multiplier) = match size.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&size[..size.len() - 1], 1024.0),
+        Some('M') => (&size[..size.len() - 1], 1024.0 * 1024.0),
+        Some('G') => (&size[..size.len() - 1], 1024.0 * 1024.0 * 1024.0),
+        _ => (size, 1.0),
+    };
+    
+    let value: f64 = number.trim().parse()
+        .map_err(|_| format!("Invalid size: {}", size))?;
+    if value <= 0.0 {
+        return Err(format!("Size must be positive: {}", size));
+    }
+    Ok((value * multiplier) as u64)
+}
+
+// Set the total bandwidth cap shared by all downloads (e.g. "5M"), or clear it with None.
+// Downloads that haven't started yet, including queued ones, use the new limit.
+#[tauri::command]
+pub async fn set_global_rate_limit(limit: Option<String>) -> Result<(), String> {
+    let mut settings = read_settings();
+    settings.global_rate_limit = match limit {
+        Some(limit) => Some(parse_byte_size(&limit)?),
+        None => None,
+    };
+    write_settings(&settings)
+}
+
+// Set the total bandwidth cap in bytes per second; same setting as set_global_rate_limit
+#[tauri::command]
+pub async fn set_speed_limit(bytes_per_sec: u64) -> Result<(), String> {
+    if bytes_per_sec == 0 {
+        return Err("Speed limit must be more than 0 bytes per second".to_string());
+    }
+    
+    let mut settings = read_settings();
+    settings.global_rate_limit = Some(bytes_per_sec);
+    write_settings(&settings)
+}
+
+// Helper function to validate an --extractor-args entry of the form "extractor:args"
+// Example: youtube:player_client=android,web
+fn validate_extractor_arg(arg: &str) -> Result<(), String> {
+    let (extractor, args) = arg.split_once(':')
+        .ok_or(format!("Extractor args must look like \"extractor:key=value\": {}", arg))?;
+    let valid_extractor = !extractor.is_empty()
+        && extractor.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+');
+    if !valid_extractor || args.trim().is_empty() {
+        return Err(format!("Extractor args must look like \"extractor:key=value\": {}", arg));
+    }
+    Ok(())
+}
+
+// Helper function to add --extractor-args for each entry
+fn apply_extractor_args(cmd: &mut Command, extractor_args: &[String]) {
+    for arg in extractor_args {
+        cmd.arg("--extractor-args").arg(arg);
+    }
+}
+
+// Helper function to check that a User-Agent or Referer, when set, is a usable header value
+fn validate_http_header(name: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(value) if value.trim().is_empty() => Err(format!("{} cannot be empty", name)),
+        Some(value) if value.contains(['\r', '\n']) => Err(format!("{} cannot contain line breaks", name)),
+        _ => Ok(()),
+    }
+}
+
+// Helper function to add --user-agent and --referer when set
+fn apply_http_headers(cmd: &mut Command, user_agent: Option<&str>, referer: Option<&str>) {
+    if let Some(user_agent) = user_agent {
+        cmd.arg("--user-agent").arg(user_agent.trim());
+    }
+    if let Some(referer) = referer {
+        cmd.arg("--referer").arg(referer.trim());
+    }
+}
+
+// Set the default User-Agent and Referer passed to every yt-dlp call (None clears them)
+#[tauri::command]
+pub async fn set_http_headers(user_agent: Option<String>, referer: Option<String>) -> Result<(), String> {
+    validate_http_header("User-Agent", user_agent.as_deref())?;
+    validate_http_header("Referer", referer.as_deref())?;
+    
+    let mut settings = read_settings();
+    settings.user_agent = user_agent;
+    settings.referer = referer;
+    write_settings(&settings)
+}
+
+// Set the default extractor args passed to every yt-dlp call
+#[tauri::command]
+pub async fn set_extractor_args(args: Vec<String>) -> Result<(), String> {
+    for arg in &args {
+        validate_extractor_arg(arg)?;
+    }
+    
+    let mut settings = read_settings();
+    settings.extractor_args = args;
+    write_settings(&settings)
+}
+
+// Video used by dry runs that check settings against a real extraction
+const DRY_RUN_TEST_URL: &str = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+
+// Helper function to check whether a failed dry run says nothing about the setting being
+// tested: the machine is offline, YouTube is throttling, or the test video is blocked here
+fn is_unverifiable_dry_run_error(stderr: &str) -> bool {
+    let unverifiable_patterns = [
+        "Unable to download webpage",
+        "Unable to download API page",
+        "Failed to resolve",
+        "getaddrinfo failed",
+        "Name or service not known",
+        "nodename nor servname",
+        "Temporary failure in name resolution",
+        "Network is unreachable",
+        "No route to host",
+        "Connection refused",
+        "Connection reset",
+        "timed out",
+        "HTTP Error 403",
+        "HTTP Error 429",
+        "HTTP Error 5",
+        "Video unavailable",
+        "not available in your country",
+        "blocked it in your country",
+        "Sign in to confirm",
+    ];
+    
+    let stderr_lower = stderr.to_lowercase();
+    unverifiable_patterns.iter().any(|pattern| stderr_lower.contains(&pattern.to_lowercase()))
+}
+
+// Save a yt-dlp -f chain (e.g. "bv[height=1080][vcodec^=vp9]+ba/bv[height=1080][vcodec^=avc1]+ba/b")
+// used by default for new downloads. Checked with a dry run against a known video; if that
+// video can't be reached the chain is saved unchecked.
+#[tauri::command]
+pub async fn save_format_preference(chain: String) -> Result<(), String> {
+    let chain = chain.trim().to_string();
+    if chain.is_empty() {
+        return Err("Format preference can't be empty".to_string());
+    }
+    
+    let output = run_yt_dlp_info_async(&[
+        "--simulate",
+        "--no-playlist",
+        "-f", &chain,
+        DRY_RUN_TEST_URL
+    ], &read_settings().auth_method).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !is_unverifiable_dry_run_error(&stderr) {
+            return Err(format!("Invalid format preference: {}", stderr.trim()));
+        }
+        eprintln!("Couldn't verify format preference, saving it anyway: {}", stderr.trim());
+    }
+    
+    let mut settings = read_settings();
+    settings.format_preference = Some(chain);
+    write_settings(&settings)
+}
+
+// Get the saved format chain, if any
+#[tauri::command]
+pub async fn load_format_preference() -> Result<Option<String>, String> {
+    Ok(read_settings().format_preference)
+}
+
+// Get the subtitle languages used when a download doesn't specify any
+#[tauri::command]
+pub async fn get_default_subtitle_langs() -> Result<Vec<String>, String> {
+    Ok(read_settings().subtitle_langs)
+}
+
+// Set the subtitle languages used when a download doesn't specify any
+#[tauri::command]
+pub async fn set_default_subtitle_langs(langs: Vec<String>) -> Result<(), String> {
+    let langs: Vec<String> = langs
+        .iter()
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+        .collect();
+    if langs.is_empty() {
+        return Err("At least one subtitle language is required".to_string());
+    }
+    
+    let mut settings = read_settings();
+    settings.subtitle_langs = langs;
+    write_settings(&settings)
+}
+
+// Get common subtitle languages as (code, name) pairs for the language picker
+#[tauri::command]
+pub async fn get_common_subtitle_languages() -> Result<Vec<(String, String)>, String> {
+    Ok(COMMON_SUBTITLE_LANGUAGES
+        .iter()
+        .map(|(code, name)| (code.to_string(), name.to_string()))
+        .collect())
+}
+
+// Get the default quality preset
+#[tauri::command]
+pub async fn get_default_quality() -> Result<Option<String>, String> {
+    Ok(read_settings().default_quality)
+}
+
+// Set the default quality preset used when a download doesn't specify one
+#[tauri::command]
+pub async fn set_default_quality(quality: String) -> Result<(), String> {
+    if !QUALITY_PRESETS.contains(&quality.as_str()) {
+        return Err(format!("Unknown quality preset: {}", quality));
+    }
+    
+    let mut settings = read_settings();
+    settings.default_quality = Some(quality);
+    write_settings(&settings)
+}
+
+// Helper function to validate YouTube URL
+#[tauri::command]
+pub fn validate_url(url: String) -> Result<VideoMetadata, String> {
+    // Basic YouTube URL validation
+    let youtube_regex = regex::Regex::new(
+        r"^(https?://)?(www\.)?(youtube\.com/(watch\?v=|shorts/)|youtu\.be/)[\w-]+"
+    ).map_err(|e| format!("Failed to create regex: {}", e))?;
+
+    // Channel and playlist pages are accepted as well
+    let is_list = matches!(classify_url_structure(&url), Some(UrlKind::Channel | UrlKind::Playlist));
+    if !youtube_regex.is_match(&url) && !is_list {
+        return Err("Invalid YouTube URL".to_string());
+    }
+
+    // For now, return a placeholder metadata
+    // In a real implementation, we would use yt-dlp to fetch actual metadata
+    Ok(VideoMetadata {
+        title: "Video Title".to_string(),
+        thumbnail: "".to_string(),
+        duration: "0:00".to_string(),
+        uploader: "Uploader".to_string(),
+    })
+}
+
+// Helper struct to hold yt-dlp path and resources directory
+struct YtDlpInfo {
+    path: String,
+    resources_dir: Option<String>,
+    bun_path: Option<String>,
+}
+
+// Helper function to find yt-dlp executable
+fn find_yt_dlp() -> Result<String, String> {
+    find_yt_dlp_with_resources().map(|info| info.path)
+}
+
+// Helper function to find yt-dlp executable and resources directory
+fn find_yt_dlp_with_resources() -> Result<YtDlpInfo, String> {
+    // First, try to find the bundled yt-dlp in the Resources directory
+    // When running as a bundled app, the executable is in .app/Contents/MacOS/
+    // and resources are in .app/Contents/Resources/
+    if let Ok(exe_path) = std::env::current_exe() {
+        let exe_str = exe_path.to_string_lossy().to_string();
+        eprintln!("DEBUG: exe_path = {}", exe_str);
+        
+        if let Some(contents_dir) = exe_path.parent().and_then(|p| p.parent()) {
+            let contents_dir_str = contents_dir.to_string_lossy().to_string();
+            eprintln!("DEBUG: contents_dir = {}", contents_dir_str);
+            
+            // Try Resources/binaries/yt-dlp first (this is where Tauri bundles resources)
+            let binaries_path = contents_dir.join("Resources").join("binaries").join("yt-dlp");
+            let binaries_path_str = binaries_path.to_string_lossy().to_string();
+            eprintln!("DEBUG: checking binaries_path = {}, exists = {}", binaries_path_str, binaries_path.exists());
+            
+            if binaries_path.exists() {
+                eprintln!("DEBUG: Found yt-dlp at {}", binaries_path_str);
+                // Return the binaries directory so bun can be found there too
+                let binaries_dir = contents_dir.join("Resources").join("binaries").to_string_lossy().to_string();
+                
+                // Check for bundled bun
+                let bun_path = contents_dir.join("Resources").join("binaries").join("bun");
+                let bun_path_str = if bun_path.exists() {
+                    eprintln!("DEBUG: Found bundled bun at {}", bun_path.to_string_lossy());
+                    Some(bun_path.to_string_lossy().to_string())
+                } else {
+                    eprintln!("DEBUG: Bundled bun not found at {}", bun_path.to_string_lossy());
+                    None
+                };
+                
+                return Ok(YtDlpInfo { 
+                    path: binaries_path_str, 
+                    resources_dir: Some(binaries_dir),
+                    bun_path: bun_path_str,
+                });
+            }
+            
+            // Try Resources/yt-dlp as fallback
+            let resources_path = contents_dir.join("Resources").join("yt-dlp");
+            let resources_path_str = resources_path.to_string_lossy().to_string();
+            eprintln!("DEBUG: checking resources_path = {}, exists = {}", resources_path_str, resources_path.exists());
+            
+            if resources_path.exists() {
+                eprintln!("DEBUG: Found yt-dlp at {}", resources_path_str);
+                let res_dir = contents_dir.join("Resources").to_string_lossy().to_string();
+                
+                // Check for bun in Resources
+                let bun_path = contents_dir.join("Resources").join("bun");
+                let bun_path_str = if bun_path.exists() {
+                    Some(bun_path.to_string_lossy().to_string())
+                } else {
+                    None
+                };
+                
+                return Ok(YtDlpInfo { 
+                    path: resources_path_str, 
+                    resources_dir: Some(res_dir),
+                    bun_path: bun_path_str,
+                });
+            }
+        }
+    }
+
+    // Fallback: try to find yt-dlp using 'which' command
+    eprintln!("DEBUG: Trying 'which' command");
+    let output = Command::new("which")
+        .arg("yt-dlp")
+        .output()
+        .map_err(|e| format!("Failed to run 'which' command: {}", e))?;
+
+    if output.status.success() {
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        eprintln!("DEBUG: 'which' found yt-dlp at {}", path);
+        if !path.is_empty() {
+            Ok(YtDlpInfo { path, resources_dir: None, bun_path: None })
+        } else {
+            Err("yt-dlp not found. Please install yt-dlp using: brew install yt-dlp".to_string())
+        }
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eprintln!("DEBUG: 'which' failed: {}", stderr);
+        Err("yt-dlp not found. Please install yt-dlp using: brew install yt-dlp".to_string())
+    }
+}
+
+// Helper function to configure command with bundled resources in PATH and JS runtime
+fn configure_command_env(cmd: &mut Command, yt_dlp_info: &YtDlpInfo) {
+    if let Some(res_dir) = &yt_dlp_info.resources_dir {
+        // Add the resources directory to PATH so yt-dlp can find bundled bun
+        let current_path = std::env::var("PATH").unwrap_or_default();
+        let new_path = format!("{}:{}", res_dir, current_path);
+        cmd.env("PATH", new_path);
+        eprintln!("DEBUG: Set PATH to include resources dir: {}", res_dir);
+    }
+    
+    // Explicitly tell yt-dlp where to find bun using --js-runtimes
+    // --no-js-runtimes clears the deno default so bun takes priority
+    if let Some(bun_path) = &yt_dlp_info.bun_path {
+        cmd.arg("--no-js-runtimes");
+        cmd.arg("--js-runtimes").arg(format!("bun:{}", bun_path));
+        eprintln!("DEBUG: Set --js-runtimes bun:{}", bun_path);
+    }
+}
+
+// Helper function to get cookies file path
+fn get_cookies_path() -> Result<String, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("cookies.txt");
+    
+    if path.exists() {
+        Ok(path.to_string_lossy().to_string())
+    } else {
+        Err("Cookies file not found. Please run: yt-dlp --cookies-from-browser chrome --cookies ~/.youtube-downloader/cookies.txt --skip-download \"https://www.youtube.com/watch?v=dQw4w9WgXcQ\"".to_string())
+    }
+}
+
+// Helper function to get the yt-dlp cache directory used for OAuth2 tokens
+fn get_oauth_cache_dir() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("yt-dlp-cache");
+    Ok(path)
+}
+
+// Helper function to get the token file written by the yt-dlp OAuth2 plugin
+fn get_oauth_token_path() -> Result<PathBuf, String> {
+    let mut path = get_oauth_cache_dir()?;
+    path.push("youtube-oauth2");
+    path.push("token_data.json");
+    Ok(path)
+}
+
+// Helper function to write stored OAuth2 tokens where the yt-dlp OAuth2 plugin expects them.
+// The plugin refreshes the access token itself once `expires` has passed.
+fn prepare_oauth_cache() -> Result<PathBuf, String> {
+    let credentials = read_credentials()?;
+    if credentials.refresh_token.is_empty() {
+        return Err("No OAuth refresh token stored".to_string());
+    }
+    
+    let token_path = get_oauth_token_path()?;
+    if let Some(parent) = token_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    let token_data = serde_json::json!({
+        "access_token": credentials.access_token,
+        "refresh_token": credentials.refresh_token,
+        "expires": credentials.expires,
+        "token_type": "Bearer",
+    });
+    fs::write(&token_path, token_data.to_string())
+        .map_err(|e| format!("Failed to write OAuth token cache: {}", e))?;
+    
+    get_oauth_cache_dir()
+}
+
+// Helper function to read tokens back from the yt-dlp OAuth2 plugin cache
+fn read_oauth_cache_tokens() -> Result<(String, String, u64), String> {
+    let content = fs::read_to_string(get_oauth_token_path()?)
+        .map_err(|e| format!("Failed to read OAuth token cache: {}", e))?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse OAuth token cache: {}", e))?;
+    
+    let access_token = json["access_token"].as_str().unwrap_or("").to_string();
+    let refresh_token = json["refresh_token"].as_str().unwrap_or("").to_string();
+    let expires = json["expires"].as_f64().unwrap_or(0.0) as u64;
+    
+    if refresh_token.is_empty() {
+        return Err("OAuth token cache has no refresh token".to_string());
+    }
+    Ok((access_token, refresh_token, expires))
+}
+
+// Helper function to persist tokens refreshed by the yt-dlp OAuth2 plugin
+fn sync_oauth_tokens() {
+    let (Ok(mut credentials), Ok((access_token, refresh_token, expires))) =
+        (read_credentials(), read_oauth_cache_tokens())
+    else {
+        return;
+    };
+    
+    if credentials.access_token != access_token || credentials.expires != expires {
+        credentials.access_token = access_token;
+        credentials.refresh_token = refresh_token;
+        credentials.expires = expires;
+        if let Err(e) = write_credentials(&credentials) {
+            eprintln!("Failed to persist refreshed OAuth tokens: {}", e);
+        }
+    }
+}
+
+// Helper function to add authentication arguments to a yt-dlp command.
+// Uses OAuth2 when selected and tokens are available, otherwise the cookies file.
+// Returns the auth method actually applied.
+fn apply_auth_args(cmd: &mut Command, auth_method: &str) -> Result<&'static str, String> {
+    if auth_method == "oauth" {
+        match prepare_oauth_cache() {
+            Ok(cache_dir) => {
+                cmd.arg("--cache-dir").arg(cache_dir);
+                cmd.args(["--username", "oauth2", "--password", ""]);
+                return Ok("oauth");
+            }
+            Err(e) => eprintln!("OAuth unavailable, falling back to cookies: {}", e),
+        }
+    }
+    
+    let cookies_path = get_cookies_path()?;
+    cmd.arg("--cookies").arg(&cookies_path);
+    Ok("cookies")
+}
+
+// Running info fetches and when the last one started, shared by every video info fetch
+struct InfoFetchLimiter {
+    state: Mutex<(usize, Option<std::time::Instant>)>,
+    released: std::sync::Condvar,
+}
+
+// Slot in the info fetch limiter, given back when dropped
+struct InfoFetchPermit;
+
+impl Drop for InfoFetchPermit {
+    fn drop(&mut self) {
+        let limiter = info_fetch_limiter();
+        limiter.state.lock().unwrap().0 -= 1;
+        limiter.released.notify_all();
+    }
+}
+
+// Helper function to get the global info fetch limiter
+fn info_fetch_limiter() -> &'static InfoFetchLimiter {
+    static LIMITER: OnceLock<InfoFetchLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| InfoFetchLimiter {
+        state: Mutex::new((0, None)),
+        released: std::sync::Condvar::new(),
+    })
+}
+
+// Helper function to wait for an info fetch slot per the settings' concurrency and spacing limits.
+// Callers queue here in no particular order until a slot frees up, so this blocks the thread
+// and must not be reached directly from an async command.
+fn acquire_info_fetch_slot(settings: &AppSettings) -> InfoFetchPermit {
+    let max_running = settings.max_concurrent_info_fetches.max(1);
+    let interval = std::time::Duration::from_millis(settings.info_fetch_interval_ms);
+    let limiter = info_fetch_limiter();
+    
+    let mut state = limiter.state.lock().unwrap();
+    loop {
+        let (running, last_start) = *state;
+        let wait = last_start
+            .map(|last| interval.saturating_sub(last.elapsed()))
+            .unwrap_or_default();
+        if running < max_running && wait.is_zero() {
+            break;
+        }
+        // Woken early when a fetch finishes; otherwise re-check once the spacing has passed
+        let timeout = if wait.is_zero() { std::time::Duration::from_secs(1) } else { wait };
+        state = limiter.released.wait_timeout(state, timeout).unwrap().0;
+    }
+    
+    state.0 += 1;
+    state.1 = Some(std::time::Instant::now());
+    InfoFetchPermit
+}
+
+// Helper function to run yt-dlp to completion with the bundled environment and authentication.
+// Doesn't wait for an info fetch slot; info fetches go through run_yt_dlp_info.
+fn run_yt_dlp(args: &[&str], auth_method: &str) -> Result<std::process::Output, String> {
+    let yt_dlp_info = find_yt_dlp_with_resources()?;
+
+    let settings = read_settings();
+    let mut cmd = Command::new(&yt_dlp_info.path);
+    configure_command_env(&mut cmd, &yt_dlp_info);
+    apply_extractor_args(&mut cmd, &settings.extractor_args);
+    apply_http_headers(&mut cmd, settings.user_agent.as_deref(), settings.referer.as_deref());
+    let applied_auth = apply_auth_args(&mut cmd, auth_method)?;
+    
+    let output = cmd
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+
+    if applied_auth == "oauth" {
+        sync_oauth_tokens();
+    }
+    
+    Ok(output)
+}
+
+// Helper function to run a metadata-only yt-dlp call once an info fetch slot is free.
+// Blocks while waiting, so async commands use run_yt_dlp_info_async.
+fn run_yt_dlp_info(args: &[&str], auth_method: &str) -> Result<std::process::Output, String> {
+    let _permit = acquire_info_fetch_slot(&read_settings());
+    run_yt_dlp(args, auth_method)
+}
+
+// Helper function to run run_yt_dlp_info on the blocking pool
+async fn run_yt_dlp_info_async(args: &[&str], auth_method: &str) -> Result<std::process::Output, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let auth_method = auth_method.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        run_yt_dlp_info(&args, &auth_method)
+    })
+    .await
+    .map_err(|e| format!("Failed to run yt-dlp: {}", e))?
+}
+
+// Helper function to fetch the yt-dlp JSON (and any warnings on stderr) for a single video
+// using the given auth method
+fn run_dump_json(url: &str, auth_method: &str) -> Result<(serde_json::Value, String), String> {
+    let output = run_yt_dlp(&["--dump-json", "--no-playlist", url], auth_method)?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    if !output.status.success() {
+        if classify_error(&stderr) == ErrorCategory::RateLimited {
+            return Err(format!(
+                "{}: YouTube is rate limiting requests (HTTP 429). Wait a while before fetching more videos, or refresh your cookies. {}",
+                RATE_LIMITED_ERROR,
+                stderr.trim()
+            ));
+        }
+        return Err(format!("yt-dlp error: {}", stderr));
+    }
+
+    let json = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    Ok((json, stderr))
+}
+
+// Helper function to fetch the yt-dlp JSON and stderr for a single video, falling back to cookies if OAuth fails
+fn fetch_video_json_with_stderr(url: &str) -> Result<(serde_json::Value, String), String> {
+    let settings = read_settings();
+    let _permit = acquire_info_fetch_slot(&settings);
+    
+    match run_dump_json(url, &settings.auth_method) {
+        Err(e) if settings.auth_method == "oauth" => {
+            eprintln!("OAuth request failed, retrying with cookies: {}", e);
+            run_dump_json(url, "cookies")
+        }
+        result => result,
+    }
+}
+
+// Helper function to fetch the yt-dlp JSON for a single video
+fn fetch_video_json(url: &str) -> Result<serde_json::Value, String> {
+    fetch_video_json_with_stderr(url).map(|(json, _)| json)
+}
+
+// Helper function to fetch the yt-dlp JSON for a single video, emitting
+// signature-extraction-warning if yt-dlp reports signature/nsig problems
+fn fetch_video_json_checked(url: &str, app: &AppHandle) -> Result<serde_json::Value, String> {
+    let (result, output) = match fetch_video_json_with_stderr(url) {
+        Ok((json, stderr)) => (Ok(json), stderr),
+        Err(e) => (Err(e.clone()), e),
+    };
+    
+    if let Some(warning) = detect_signature_warning(&output) {
+        emit_signature_warning(app, None, &warning);
+    }
+    result
+}
+
+// Helper function to run fetch_video_json on the blocking pool, since waiting for an
+// info fetch slot would otherwise hold up an async runtime worker
+async fn fetch_video_json_async(url: &str) -> Result<serde_json::Value, String> {
+    let url = url.to_string();
+    tauri::async_runtime::spawn_blocking(move || fetch_video_json(&url))
+        .await
+        .map_err(|e| format!("Failed to fetch video info: {}", e))?
+}
+
+// Helper function to run fetch_video_json_checked on the blocking pool
+async fn fetch_video_json_checked_async(url: &str, app: &AppHandle) -> Result<serde_json::Value, String> {
+    let url = url.to_string();
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || fetch_video_json_checked(&url, &app))
+        .await
+        .map_err(|e| format!("Failed to fetch video info: {}", e))?
+}
+
+// A yt-dlp signature/nsig extraction problem, which usually means yt-dlp is out of date
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignatureExtractionWarning {
+    // The warning line yt-dlp printed
+    pub message: String,
+    pub yt_dlp_version: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+// Helper function to find a signature/nsig extraction warning in yt-dlp stderr
+// Example: WARNING: [youtube] dQw4w9WgXcQ: nsig extraction failed: Some formats may be missing
+fn detect_signature_warning(stderr: &str) -> Option<SignatureExtractionWarning> {
+    let line = stderr.lines().find(|line| {
+        let line = line.to_lowercase();
+        SIGNATURE_WARNING_PATTERNS.iter().any(|pattern| line.contains(pattern))
+    })?;
+    
+    Some(SignatureExtractionWarning {
+        message: line.trim().to_string(),
+        yt_dlp_version: get_yt_dlp_version().ok(),
+        suggestions: vec![
+            "Update yt-dlp to the latest version".to_string(),
+            "Set extractor args to \"youtube:player_client=android\"".to_string(),
+        ],
+    })
+}
+
+// Helper function to emit a signature warning, optionally tied to a download. The
+// yt-dlp freshness report is included so the UI can prompt for an update; it is looked
+// up in a background task since it may go to GitHub.
+fn emit_signature_warning(app: &AppHandle, id: Option<&str>, warning: &SignatureExtractionWarning) {
+    let app = app.clone();
+    let id = id.map(|id| id.to_string());
+    let warning = warning.clone();
+    tauri::async_runtime::spawn(async move {
+        let update = yt_dlp_freshness().await
+            .map_err(|e| eprintln!("Failed to check yt-dlp version: {}", e))
+            .ok();
+        let emit_result = app.emit("signature-extraction-warning", serde_json::json!({
+            "id": id,
+            "warning": warning,
+            "update": update
+        }));
+        if let Err(e) = emit_result {
+            eprintln!("Failed to emit signature-extraction-warning: {}", e);
+        }
+    });
+}
+
+// Helper function to list the entries of a playlist or channel without resolving each video
+fn fetch_flat_playlist(url: &str) -> Result<Vec<serde_json::Value>, String> {
+    let output = run_yt_dlp_info(
+        &["--flat-playlist", "--dump-single-json", url],
+        &read_settings().auth_method,
+    )?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("yt-dlp error: {}", stderr));
+    }
+    
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    
+    Ok(json["entries"].as_array().cloned().unwrap_or_default())
+}
+
+// Helper function to find ffmpeg executable
+fn find_ffmpeg() -> Option<String> {
+    // Common ffmpeg locations on macOS
     let common_paths = [
         "/opt/homebrew/bin/ffmpeg",      // Homebrew on Apple Silicon
         "/usr/local/bin/ffmpeg",          // Homebrew on Intel
         "/usr/bin/ffmpeg",                // System PATH
     ];
     
-    for path in &common_paths {
-        if std::path::Path::new(path).exists() {
-            return Some(path.to_string());
+    for path in &common_paths {
+        if std::path::Path::new(path).exists() {
+            return Some(path.to_string());
+        }
+    }
+    
+    // Try to find using 'which' command
+    if let Ok(output) = Command::new("which").arg("ffmpeg").output() {
+        if output.status.success() {
+            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !path.is_empty() {
+                return Some(path);
+            }
+        }
+    }
+    
+    None
+}
+
+// Helper function to get the installed yt-dlp version (a date string like "2025.01.15")
+fn get_yt_dlp_version() -> Result<String, String> {
+    let yt_dlp_path = find_yt_dlp()?;
+    let output = Command::new(&yt_dlp_path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    
+    if !output.status.success() {
+        return Err("Failed to get yt-dlp version".to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Helper function to get the ffmpeg version from the first line of `ffmpeg -version`
+// Example: ffmpeg version 7.1 Copyright (c) 2000-2024 the FFmpeg developers
+fn get_ffmpeg_version(ffmpeg_path: &str) -> Option<String> {
+    let output = Command::new(ffmpeg_path).arg("-version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .next()?
+        .strip_prefix("ffmpeg version ")?
+        .split_whitespace()
+        .next()
+        .map(|v| v.to_string())
+}
+
+// Helper function to find a JS runtime on PATH that yt-dlp can use, as (name, path)
+fn find_system_js_runtime() -> Option<(String, String)> {
+    ["bun", "deno", "node"].iter().find_map(|runtime| {
+        let output = Command::new("which").arg(runtime).output().ok()?;
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !path.is_empty() {
+            Some((runtime.to_string(), path))
+        } else {
+            None
+        }
+    })
+}
+
+// Result of check_js_runtime
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JsRuntimeStatus {
+    // "bun", "deno", or "node"; None when no runtime was found
+    pub runtime: Option<String>,
+    pub path: Option<String>,
+    pub bundled: bool,
+    pub version: Option<String>,
+    // The runtime starts and yt-dlp extracted signatures for the test video without warnings.
+    // When the test video couldn't be reached this only means the runtime starts.
+    pub functional: bool,
+    // Whether the signature extraction test could run at all
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+// Check that the JS runtime yt-dlp uses actually runs and that nsig extraction works with it
+#[tauri::command]
+pub async fn check_js_runtime() -> Result<JsRuntimeStatus, String> {
+    let yt_dlp_info = find_yt_dlp_with_resources()?;
+    
+    // The bundled bun is passed to yt-dlp explicitly; otherwise yt-dlp picks one from PATH
+    let (runtime, path, bundled) = match &yt_dlp_info.bun_path {
+        Some(bun_path) => (Some("bun".to_string()), Some(bun_path.clone()), true),
+        None => match find_system_js_runtime() {
+            Some((runtime, path)) => (Some(runtime), Some(path), false),
+            None => (None, None, false),
+        },
+    };
+    
+    let mut status = JsRuntimeStatus {
+        runtime,
+        path: path.clone(),
+        bundled,
+        version: None,
+        functional: false,
+        verified: false,
+        error: None,
+    };
+    let Some(path) = path else {
+        status.error = Some("No JavaScript runtime found".to_string());
+        return Ok(status);
+    };
+    
+    match Command::new(&path).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            status.version = Some(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            status.error = Some(format!("{} --version failed: {}", path, stderr.trim()));
+            return Ok(status);
+        }
+        Err(e) => {
+            status.error = Some(format!("Failed to run {}: {}", path, e));
+            return Ok(status);
+        }
+    }
+    
+    // Resolving formats for a real video exercises signature and nsig extraction
+    let output = run_yt_dlp_info_async(&[
+        "--simulate",
+        "--no-playlist",
+        DRY_RUN_TEST_URL
+    ], &read_settings().auth_method).await?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let runtime_warning = stderr.lines()
+        .find(|line| line.to_lowercase().contains("javascript runtime"))
+        .map(|line| line.trim().to_string());
+    
+    // Being offline or blocked says nothing about the runtime, which did start
+    if !output.status.success() && is_unverifiable_dry_run_error(&stderr) {
+        status.functional = true;
+        status.error = Some(format!("Couldn't reach the test video to check signature extraction: {}", stderr.trim()));
+        return Ok(status);
+    }
+    status.verified = true;
+    if !output.status.success() {
+        status.error = Some(format!("yt-dlp error: {}", stderr.trim()));
+    } else if let Some(warning) = detect_signature_warning(&stderr) {
+        status.error = Some(warning.message);
+    } else if let Some(warning) = runtime_warning {
+        status.error = Some(warning);
+    } else {
+        status.functional = true;
+    }
+    Ok(status)
+}
+
+// Health check of external tools the app relies on
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyStatus {
+    pub yt_dlp_found: bool,
+    pub yt_dlp_version: Option<String>,
+    pub ffmpeg_found: bool,
+    pub ffmpeg_version: Option<String>,
+    pub js_runtime_found: bool,
+    pub js_runtime: Option<String>,
+    pub install_hints: Vec<String>,
+}
+
+// Check that yt-dlp, ffmpeg, and a JS runtime are available
+#[tauri::command]
+pub async fn check_dependencies() -> Result<DependencyStatus, String> {
+    let (yt_dlp_hint, ffmpeg_hint, js_runtime_hint) = if cfg!(target_os = "macos") {
+        ("brew install yt-dlp", "brew install ffmpeg", "curl -fsSL https://bun.sh/install | bash")
+    } else if cfg!(target_os = "windows") {
+        ("winget install yt-dlp.yt-dlp", "winget install Gyan.FFmpeg", "powershell -c \"irm bun.sh/install.ps1 | iex\"")
+    } else {
+        ("pip install -U yt-dlp", "sudo apt install ffmpeg", "curl -fsSL https://bun.sh/install | bash")
+    };
+    
+    let yt_dlp_info = find_yt_dlp_with_resources().ok();
+    let yt_dlp_version = get_yt_dlp_version().ok();
+    
+    let ffmpeg_path = find_ffmpeg();
+    let ffmpeg_version = ffmpeg_path.as_deref().and_then(get_ffmpeg_version);
+    
+    let js_runtime = yt_dlp_info
+        .as_ref()
+        .and_then(|info| info.bun_path.as_ref())
+        .map(|bun_path| format!("bun (bundled, {})", bun_path))
+        .or_else(|| find_system_js_runtime().map(|(runtime, path)| format!("{} ({})", runtime, path)));
+    
+    let mut install_hints = Vec::new();
+    if yt_dlp_info.is_none() {
+        install_hints.push(format!("yt-dlp not found. Install it with: {}", yt_dlp_hint));
+    }
+    if ffmpeg_path.is_none() {
+        install_hints.push(format!("ffmpeg not found (needed to merge video and audio). Install it with: {}", ffmpeg_hint));
+    }
+    if js_runtime.is_none() {
+        install_hints.push(format!("No JavaScript runtime found (needed for YouTube signature extraction). Install bun with: {}", js_runtime_hint));
+    }
+    
+    Ok(DependencyStatus {
+        yt_dlp_found: yt_dlp_info.is_some(),
+        yt_dlp_version,
+        ffmpeg_found: ffmpeg_path.is_some(),
+        ffmpeg_version,
+        js_runtime_found: js_runtime.is_some(),
+        js_runtime,
+        install_hints,
+    })
+}
+
+// How the installed yt-dlp compares to the recommended and latest releases
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum YtDlpFreshness {
+    UpToDate,
+    UpdateAvailable,
+    // Older than RECOMMENDED_YT_DLP_VERSION; extraction is likely to fail
+    TooOld,
+}
+
+// Result of check_yt_dlp_freshness
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct YtDlpFreshnessReport {
+    pub status: YtDlpFreshness,
+    pub installed: String,
+    pub recommended: String,
+    // Latest GitHub release, if it could be looked up
+    pub latest: Option<String>,
+}
+
+// Latest yt-dlp release cached on disk
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct YtDlpReleaseCache {
+    latest: String,
+    checked_at: u64,
+}
+
+// Helper function to get the latest yt-dlp release tag from GitHub, cached for a day
+// in ~/.youtube-downloader/yt-dlp-release.json
+async fn get_latest_yt_dlp_release() -> Result<String, String> {
+    let mut cache_path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    cache_path.push(".youtube-downloader");
+    fs::create_dir_all(&cache_path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    cache_path.push("yt-dlp-release.json");
+    
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let cached: Option<YtDlpReleaseCache> = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    if let Some(cached) = cached {
+        if now.saturating_sub(cached.checked_at) < YT_DLP_RELEASE_CACHE_SECS {
+            return Ok(cached.latest);
+        }
+    }
+    
+    let response = reqwest::Client::new()
+        .get("https://api.github.com/repos/yt-dlp/yt-dlp/releases/latest")
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "youtube-downloader")
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to check the latest yt-dlp release: {}", e))?;
+    
+    let body = response.bytes().await
+        .map_err(|e| format!("Failed to check the latest yt-dlp release: {}", e))?;
+    let json: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
+    let latest = json["tag_name"].as_str()
+        .ok_or("GitHub response has no release tag")?
+        .to_string();
+    
+    let cache = YtDlpReleaseCache { latest: latest.clone(), checked_at: now };
+    if let Ok(json) = serde_json::to_string_pretty(&cache) {
+        let _ = fs::write(&cache_path, json);
+    }
+    Ok(latest)
+}
+
+// Compare the installed yt-dlp against the recommended version and the latest release.
+// Versions are dates like "2025.10.22", so they compare correctly as strings.
+#[tauri::command]
+pub async fn check_yt_dlp_freshness() -> Result<YtDlpFreshnessReport, String> {
+    yt_dlp_freshness().await
+}
+
+// Helper function to build the report for check_yt_dlp_freshness
+async fn yt_dlp_freshness() -> Result<YtDlpFreshnessReport, String> {
+    let installed = get_yt_dlp_version()?;
+    let latest = match get_latest_yt_dlp_release().await {
+        Ok(latest) => Some(latest),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    };
+    
+    let status = if installed.as_str() < RECOMMENDED_YT_DLP_VERSION {
+        YtDlpFreshness::TooOld
+    } else if latest.as_ref().is_some_and(|latest| latest > &installed) {
+        YtDlpFreshness::UpdateAvailable
+    } else {
+        YtDlpFreshness::UpToDate
+    };
+    
+    Ok(YtDlpFreshnessReport {
+        status,
+        installed,
+        recommended: RECOMMENDED_YT_DLP_VERSION.to_string(),
+        latest,
+    })
+}
+
+// Helper function to find ffprobe, which ships alongside ffmpeg
+fn find_ffprobe() -> Option<String> {
+    let ffmpeg_path = find_ffmpeg()?;
+    let ffprobe_path = std::path::Path::new(&ffmpeg_path).with_file_name("ffprobe");
+    if ffprobe_path.exists() {
+        Some(ffprobe_path.to_string_lossy().to_string())
+    } else {
+        None
+    }
+}
+
+// Helper function to get a media file's duration in seconds using ffprobe
+fn get_media_duration(path: &str) -> Result<f64, String> {
+    let ffprobe_path = find_ffprobe().ok_or("ffprobe not found")?;
+    let output = Command::new(&ffprobe_path)
+        .args([
+            "-v", "error",
+            "-show_entries", "format=duration",
+            "-of", "default=noprint_wrappers=1:nokey=1",
+            path
+        ])
+        .output()
+        .map_err(|e| format!("Failed to execute ffprobe: {}", e))?;
+    
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .map_err(|_| format!("Failed to read duration of {}", path))
+}
+
+// Helper function to list a media file's stream types ("video", "audio", ...) using ffprobe
+fn get_stream_types(path: &str) -> Result<Vec<String>, String> {
+    let ffprobe_path = find_ffprobe().ok_or("ffprobe not found")?;
+    let output = Command::new(&ffprobe_path)
+        .args([
+            "-v", "error",
+            "-show_entries", "stream=codec_type",
+            "-of", "csv=p=0",
+            path
+        ])
+        .output()
+        .map_err(|e| format!("Failed to execute ffprobe: {}", e))?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ffprobe error: {}", stderr.trim()));
+    }
+    
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+// Real container/codec details of a local media file, as reported by ffprobe
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MediaProbe {
+    pub container: String,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub fps: Option<f64>,
+    pub duration: Option<f64>,
+    // Overall bitrate in bits per second
+    pub bitrate: Option<u64>,
+}
+
+// Helper function to probe a media file's container and streams using ffprobe
+fn probe_media(path: &str) -> Result<MediaProbe, String> {
+    let ffprobe_path = find_ffprobe().ok_or("ffprobe not found")?;
+    let output = Command::new(&ffprobe_path)
+        .args([
+            "-v", "error",
+            "-show_format",
+            "-show_streams",
+            "-of", "json",
+            path
+        ])
+        .output()
+        .map_err(|e| format!("Failed to execute ffprobe: {}", e))?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ffprobe error: {}", stderr.trim()));
+    }
+    
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+    let format = &json["format"];
+    
+    // format_name is a list like "mov,mp4,m4a,3gp,3g2,mj2"; prefer the entry matching the extension
+    let format_name = format["format_name"].as_str().unwrap_or_default();
+    let ext = std::path::Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let container = format_name
+        .split(',')
+        .find(|name| *name == ext)
+        .or_else(|| format_name.split(',').next())
+        .unwrap_or_default()
+        .to_string();
+    
+    let mut probe = MediaProbe {
+        container,
+        duration: format["duration"].as_str().and_then(|d| d.parse().ok()),
+        bitrate: format["bit_rate"].as_str().and_then(|b| b.parse().ok()),
+        ..Default::default()
+    };
+    
+    for stream in json["streams"].as_array().into_iter().flatten() {
+        let codec = stream["codec_name"].as_str().map(|c| c.to_string());
+        match stream["codec_type"].as_str() {
+            // Cover art is reported as a video stream too; skip it
+            Some("video") if probe.video_codec.is_none()
+                && stream["disposition"]["attached_pic"].as_u64() != Some(1) =>
+            {
+                probe.video_codec = codec;
+                probe.width = stream["width"].as_u64();
+                probe.height = stream["height"].as_u64();
+                // Frame rate is a fraction like "30000/1001"
+                probe.fps = stream["r_frame_rate"].as_str().and_then(|rate| {
+                    let (num, den) = rate.split_once('/')?;
+                    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+                    (den > 0.0).then(|| num / den)
+                });
+            }
+            Some("audio") if probe.audio_codec.is_none() => probe.audio_codec = codec,
+            _ => {}
+        }
+    }
+    
+    Ok(probe)
+}
+
+// Helper function to split a media file into equal parts of at most max_secs seconds.
+// Parts are named "<title> - part N.<ext>" next to the original, which is kept.
+fn split_by_duration(app: &AppHandle, id: &str, path: &str, max_secs: u64) -> Result<Vec<String>, String> {
+    let ffmpeg_path = find_ffmpeg().ok_or("ffmpeg not found")?;
+    let duration = get_media_duration(path)?;
+    if duration <= max_secs as f64 {
+        return Ok(Vec::new());
+    }
+    
+    let parts = (duration / max_secs as f64).ceil() as u64;
+    let segment = duration / parts as f64;
+    
+    let path_obj = std::path::Path::new(path);
+    let stem = path_obj.file_stem().ok_or("Invalid file path")?.to_string_lossy().to_string();
+    let ext = path_obj.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    let parent = path_obj.parent().ok_or("Invalid file path")?;
+    
+    let mut files = Vec::new();
+    for part in 0..parts {
+        let part_path = parent.join(format!("{} - part {}.{}", stem, part + 1, ext));
+        let output = Command::new(&ffmpeg_path)
+            .args(["-y", "-v", "error"])
+            .arg("-ss").arg(format!("{:.3}", segment * part as f64))
+            .arg("-t").arg(format!("{:.3}", segment))
+            .arg("-i").arg(path)
+            .args(["-c", "copy", "-map", "0"])
+            .arg(&part_path)
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+        
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("ffmpeg error: {}", stderr));
+        }
+        
+        files.push(part_path.to_string_lossy().to_string());
+        let emit_result = app.emit("split-progress", serde_json::json!({
+            "id": id,
+            "part": part + 1,
+            "total": parts
+        }));
+        if let Err(e) = emit_result {
+            eprintln!("Failed to emit split-progress: {}", e);
+        }
+    }
+    
+    Ok(files)
+}
+
+// Helper function to re-encode a video with its "<name>.<lang>.srt" sidecar burned in.
+// Writes "<name>-hardsub.<ext>" next to the original, which is kept.
+fn burn_subtitles(app: &AppHandle, id: &str, path: &str, lang: &str) -> Result<String, String> {
+    let path_obj = std::path::Path::new(path);
+    let stem = path_obj.file_stem().ok_or("Invalid file path")?.to_string_lossy().to_string();
+    let ext = path_obj.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "mp4".to_string());
+    let subtitle_path = path_obj.with_file_name(format!("{}.{}.srt", stem, lang));
+    if !subtitle_path.exists() {
+        return Err(format!("No {} subtitles were downloaded for this video", lang));
+    }
+    let output_path = path_obj
+        .with_file_name(format!("{}-hardsub.{}", stem, ext))
+        .to_string_lossy()
+        .to_string();
+    
+    // The subtitles filter needs its path escaped for ffmpeg's filter syntax; a copy in
+    // the temp dir keeps that to the directory part instead of arbitrary video titles
+    let temp_subtitles = std::env::temp_dir().join(format!("youtube-downloader-{}.srt", id));
+    fs::copy(&subtitle_path, &temp_subtitles)
+        .map_err(|e| format!("Failed to copy subtitles: {}", e))?;
+    let filter_path = temp_subtitles
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "\\'");
+    
+    let args = vec![
+        "-i".to_string(), path.to_string(),
+        "-vf".to_string(), format!("subtitles='{}'", filter_path),
+        "-c:a".to_string(), "copy".to_string(),
+        output_path.clone(),
+    ];
+    let duration = get_media_duration(path).unwrap_or(0.0);
+    let result = run_ffmpeg_with_progress(app, "burn-subtitles-progress", id, &args, duration);
+    let _ = fs::remove_file(&temp_subtitles);
+    result?;
+    
+    Ok(output_path)
+}
+
+// Helper function to parse an ffmpeg timestamp ("HH:MM:SS.micros") into seconds
+fn parse_ffmpeg_time(time: &str) -> Option<f64> {
+    let mut parts = time.trim().split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+// Helper function to run ffmpeg and emit progress events parsed from its -progress output.
+// `duration` is the expected output length in seconds, used to compute the percentage.
+fn run_ffmpeg_with_progress(
+    app: &AppHandle,
+    event: &str,
+    id: &str,
+    args: &[String],
+    duration: f64,
+) -> Result<(), String> {
+    use std::io::{BufRead, BufReader};
+    
+    let ffmpeg_path = find_ffmpeg().ok_or("ffmpeg not found")?;
+    let mut child = Command::new(&ffmpeg_path)
+        .args(["-y", "-v", "error", "-nostats", "-progress", "pipe:1"])
+        .args(args)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+    
+    // Drain stderr on its own thread so a chatty ffmpeg can't block on a full pipe
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let stderr_handle = std::thread::spawn(move || {
+        let mut errors = String::new();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            errors.push_str(&line);
+            errors.push('\n');
+        }
+        errors
+    });
+    
+    // -progress writes key=value lines such as "frame=120" and "out_time=00:00:04.000000"
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let mut frame = 0u64;
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        if let Some(value) = line.strip_prefix("frame=") {
+            frame = value.trim().parse().unwrap_or(frame);
+        } else if let Some(value) = line.strip_prefix("out_time=") {
+            if let Some(secs) = parse_ffmpeg_time(value) {
+                let progress = if duration > 0.0 { (secs / duration * 100.0).min(100.0) } else { 0.0 };
+                let emit_result = app.emit(event, serde_json::json!({
+                    "id": id,
+                    "progress": progress,
+                    "frame": frame
+                }));
+                if let Err(e) = emit_result {
+                    eprintln!("Failed to emit {}: {}", event, e);
+                }
+            }
+        }
+    }
+    
+    let status = child.wait().map_err(|e| format!("ffmpeg failed: {}", e))?;
+    let errors = stderr_handle.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!("ffmpeg error: {}", errors.trim()));
+    }
+    
+    Ok(())
+}
+
+// Helper function to build VideoInfo from yt-dlp's JSON output
+fn parse_video_info(json: &serde_json::Value) -> VideoInfo {
+    VideoInfo {
+        id: json["id"].as_str().unwrap_or("").to_string(),
+        title: json["title"].as_str().unwrap_or("").to_string(),
+        description: json["description"].as_str().unwrap_or("").to_string(),
+        duration: json["duration"].as_u64().unwrap_or(0),
+        uploader: json["uploader"].as_str().unwrap_or("").to_string(),
+        thumbnail: json["thumbnail"].as_str().unwrap_or("").to_string(),
+        channel_id: json["channel_id"].as_str().unwrap_or("").to_string(),
+    }
+}
+
+// How long fetched video info is reused for the same URL
+const VIDEO_INFO_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+// Helper function to get the in-memory cache of video info by URL, with when it was fetched
+fn video_info_cache() -> &'static Mutex<HashMap<String, (std::time::Instant, VideoInfo)>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, (std::time::Instant, VideoInfo)>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Helper function to get the info for a video, reusing a fetch from the last
+// VIDEO_INFO_CACHE_TTL. Otherwise waits for an info fetch slot, so it blocks.
+fn cached_video_info(url: &str, app: &AppHandle) -> Result<VideoInfo, String> {
+    if let Some((fetched_at, info)) = video_info_cache().lock().unwrap().get(url) {
+        if fetched_at.elapsed() < VIDEO_INFO_CACHE_TTL {
+            return Ok(info.clone());
+        }
+    }
+    
+    let info = parse_video_info(&fetch_video_json_checked(url, app)?);
+    let mut cache = video_info_cache().lock().unwrap();
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < VIDEO_INFO_CACHE_TTL);
+    cache.insert(url.to_string(), (std::time::Instant::now(), info.clone()));
+    Ok(info)
+}
+
+// Get video info using yt-dlp
+#[tauri::command]
+pub async fn get_video_info(url: String, app: AppHandle) -> Result<VideoInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || cached_video_info(&url, &app))
+        .await
+        .map_err(|e| format!("Failed to fetch video info: {}", e))?
+}
+
+// Get video info for many URLs concurrently, up to the max_concurrent_info_fetches setting,
+// reusing recently fetched info. Results are returned in the same order as the URLs; one
+// failure doesn't fail the batch.
+#[tauri::command]
+pub async fn get_video_info_batch(urls: Vec<String>, app: AppHandle) -> Result<Vec<Result<VideoInfo, String>>, String> {
+    // Only start as many fetches as may run at once, rather than parking a blocking
+    // thread per URL on the info fetch limiter
+    let batch_size = read_settings().max_concurrent_info_fetches.max(1);
+    let mut results = Vec::with_capacity(urls.len());
+    for batch in urls.chunks(batch_size) {
+        let tasks: Vec<_> = batch.iter().cloned().map(|url| {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || cached_video_info(&url, &app))
+        }).collect();
+        
+        for task in tasks {
+            results.push(task.await.map_err(|e| format!("Failed to fetch video info: {}", e))?);
+        }
+    }
+    Ok(results)
+}
+
+// Combined response for video info, formats, and subtitles
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CombinedVideoInfo {
+    pub info: VideoInfo,
+    pub formats: Vec<VideoFormat>,
+    pub subtitles: Vec<Subtitle>,
+}
+
+// Get video info, formats, and subtitles in a single yt-dlp call (faster)
+#[tauri::command]
+pub async fn get_video_info_combined(url: String, app: AppHandle) -> Result<CombinedVideoInfo, String> {
+    let json = fetch_video_json_checked_async(&url, &app).await?;
+
+    // Extract video info
+    let info = parse_video_info(&json);
+
+    // Extract formats
+    let formats = json["formats"].as_array()
+        .map(|format_array| format_array.iter().filter_map(parse_format).collect())
+        .unwrap_or_default();
+
+    // Extract subtitles
+    let subtitles = parse_subtitles(&json, read_settings().subtitle_mode);
+
+    Ok(CombinedVideoInfo { info, formats, subtitles })
+}
+
+// Get available formats
+#[tauri::command]
+pub async fn get_available_formats(url: String, app: AppHandle) -> Result<Vec<VideoFormat>, String> {
+    // Use --dump-json to get JSON output (formats are included in the video info)
+    let json = fetch_video_json_checked_async(&url, &app).await?;
+
+    let formats = json["formats"].as_array()
+        .ok_or("No formats found")?;
+
+    let video_formats = formats.iter().filter_map(parse_format).collect();
+
+    Ok(video_formats)
+}
+
+// Formats split by stream type for the format picker
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupedFormats {
+    // Formats with both audio and video
+    pub combined: Vec<VideoFormat>,
+    pub video_only: Vec<VideoFormat>,
+    pub audio_only: Vec<VideoFormat>,
+    // Format string yt-dlp uses by default to pick and merge the best video and audio
+    pub recommended: String,
+}
+
+// Helper function to get a format's height from its resolution (e.g. "1920x1080" -> 1080)
+fn format_height(format: &VideoFormat) -> u32 {
+    format.resolution
+        .split_once('x')
+        .and_then(|(_, height)| height.parse().ok())
+        .unwrap_or(0)
+}
+
+// Get available formats grouped into combined, video-only, and audio-only, best first
+#[tauri::command]
+pub async fn get_formats_grouped(url: String) -> Result<GroupedFormats, String> {
+    let json = fetch_video_json_async(&url).await?;
+    let formats = json["formats"].as_array()
+        .ok_or("No formats found")?;
+    
+    let mut grouped = GroupedFormats {
+        combined: Vec::new(),
+        video_only: Vec::new(),
+        audio_only: Vec::new(),
+        recommended: DEFAULT_FORMAT.to_string(),
+    };
+    for format in formats.iter().filter_map(parse_format) {
+        if format.is_video_only {
+            grouped.video_only.push(format);
+        } else if format.is_audio_only {
+            grouped.audio_only.push(format);
+        } else {
+            grouped.combined.push(format);
+        }
+    }
+    
+    // Video by resolution then frame rate; audio by size, the best proxy for bitrate we keep
+    let by_quality = |a: &VideoFormat, b: &VideoFormat| {
+        format_height(b).cmp(&format_height(a)).then(b.fps.cmp(&a.fps))
+    };
+    grouped.combined.sort_by(by_quality);
+    grouped.video_only.sort_by(by_quality);
+    grouped.audio_only.sort_by_key(|f| std::cmp::Reverse(f.filesize));
+    
+    Ok(grouped)
+}
+
+// Helper function to convert a yt-dlp format entry into a VideoFormat.
+// Skips storyboards and other entries without any audio or video stream.
+fn parse_format(format: &serde_json::Value) -> Option<VideoFormat> {
+    let ext = format["ext"].as_str()?;
+    let format_note = format["format_note"].as_str().unwrap_or("");
+    if ext == "mhtml" || format_note.contains("storyboard") {
+        return None;
+    }
+    
+    let vcodec = format["vcodec"].as_str().unwrap_or("").to_string();
+    let acodec = format["acodec"].as_str().unwrap_or("").to_string();
+    let is_video_only = acodec == "none" && vcodec != "none";
+    let is_audio_only = vcodec == "none" && acodec != "none";
+    if vcodec == "none" && acodec == "none" {
+        return None;
+    }
+    
+    // DASH streams often have no resolution string, so build one from the dimensions
+    let mut resolution = format["resolution"].as_str().unwrap_or("").to_string();
+    if resolution.is_empty() {
+        if let (Some(width), Some(height)) = (format["width"].as_u64(), format["height"].as_u64()) {
+            resolution = format!("{}x{}", width, height);
+        }
+    }
+    
+    Some(VideoFormat {
+        id: format["format_id"].as_str().unwrap_or("").to_string(),
+        ext: ext.to_string(),
+        resolution,
+        fps: format["fps"].as_f64().unwrap_or(0.0) as u32,
+        filesize: format["filesize"].as_u64(),
+        vcodec,
+        acodec,
+        is_video_only,
+        is_audio_only,
+    })
+}
+
+// Get the full yt-dlp JSON for a video, for bug reports (requires debug mode)
+#[tauri::command]
+pub async fn get_raw_video_json(url: String) -> Result<String, String> {
+    if !read_settings().debug_mode {
+        return Err("Raw video JSON is only available in debug mode".to_string());
+    }
+    
+    let json = fetch_video_json_async(&url).await?;
+    serde_json::to_string_pretty(&json)
+        .map_err(|e| format!("Failed to serialize JSON: {}", e))
+}
+
+// One sprite sheet of a storyboard, covering `start` to `start + duration` seconds
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoryboardSheet {
+    pub path: String,
+    pub start: f64,
+    pub duration: f64,
+}
+
+// Storyboard sprite sheets and their tile layout, for timeline hover previews
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Storyboard {
+    pub sheets: Vec<StoryboardSheet>,
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    // Seconds of video covered by each tile
+    pub frame_interval: f64,
+}
+
+// Helper function to fetch a remote jpg/webp image and save it as jpg using ffmpeg
+fn download_image_as_jpg(url: &str, dest: &std::path::Path) -> Result<(), String> {
+    let ffmpeg_path = find_ffmpeg().ok_or("ffmpeg not found")?;
+    let output = Command::new(&ffmpeg_path)
+        .args(["-y", "-v", "error", "-i", url, "-frames:v", "1"])
+        .arg(dest)
+        .output()
+        .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ffmpeg error: {}", stderr.trim()));
+    }
+    
+    Ok(())
+}
+
+// A thumbnail offered for a video
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Thumbnail {
+    pub id: String,
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    // YouTube's name for the size (see THUMBNAIL_QUALITIES), if recognized
+    pub quality: Option<String>,
+}
+
+// Helper function to parse the thumbnails array from yt-dlp JSON
+fn parse_thumbnails(json: &serde_json::Value) -> Vec<Thumbnail> {
+    json["thumbnails"].as_array()
+        .map(|thumbnails| thumbnails.iter().filter_map(|t| {
+            let url = t["url"].as_str()?.to_string();
+            // Example: https://i.ytimg.com/vi/VIDEO_ID/maxresdefault.jpg
+            let file_stem = url.rsplit('/').next().unwrap_or("").split('.').next().unwrap_or("");
+            let quality = THUMBNAIL_QUALITIES.iter()
+                .find(|(_, stem)| *stem == file_stem)
+                .map(|(name, _)| name.to_string());
+            Some(Thumbnail {
+                id: t["id"].as_str().map(str::to_string).unwrap_or_else(|| t["id"].to_string()),
+                url,
+                width: t["width"].as_u64().map(|w| w as u32),
+                height: t["height"].as_u64().map(|h| h as u32),
+                quality,
+            })
+        }).collect())
+        .unwrap_or_default()
+}
+
+// Helper function to pick the thumbnail for a quality name, falling back to the next
+// smaller size and finally to the largest thumbnail available
+fn select_thumbnail<'a>(thumbnails: &'a [Thumbnail], quality: &str) -> Option<&'a Thumbnail> {
+    let requested = THUMBNAIL_QUALITIES.iter().position(|(name, _)| *name == quality)?;
+    THUMBNAIL_QUALITIES[..=requested].iter().rev()
+        .find_map(|(name, _)| thumbnails.iter().find(|t| t.quality.as_deref() == Some(*name)))
+        .or_else(|| thumbnails.iter().max_by_key(|t| t.width.unwrap_or(0) * t.height.unwrap_or(0)))
+}
+
+// Get the thumbnails available for a video
+#[tauri::command]
+pub async fn get_thumbnails(url: String) -> Result<Vec<Thumbnail>, String> {
+    let json = fetch_video_json_async(&url).await?;
+    Ok(parse_thumbnails(&json))
+}
+
+// Helper function to save a thumbnail as "<name>.jpg" next to a downloaded video
+fn save_thumbnail(thumbnail_url: &str, video_path: &str) -> Result<String, String> {
+    let dest = std::path::Path::new(video_path).with_extension("jpg");
+    download_image_as_jpg(thumbnail_url, &dest)?;
+    Ok(dest.to_string_lossy().to_string())
+}
+
+// Helper function to detect a thumbnail's image type from its first bytes
+fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else {
+        None
+    }
+}
+
+// Helper function to get the path a thumbnail URL is cached at
+fn get_thumbnail_cache_path(url: &str) -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("thumbnails");
+    
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    
+    path.push(blake3::hash(url.as_bytes()).to_hex().to_string());
+    Ok(path)
+}
+
+// Download a thumbnail (jpg or webp) and return it as a data URI, so the webview can show
+// it without loading remote images. Thumbnails are cached on disk by URL.
+#[tauri::command]
+pub async fn fetch_thumbnail_base64(url: String) -> Result<String, String> {
+    use base64::Engine;
+    
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Invalid thumbnail URL: {}", url));
+    }
+    
+    let cache_path = get_thumbnail_cache_path(&url)?;
+    let bytes = match fs::read(&cache_path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let mut response = reqwest::get(&url).await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Failed to download thumbnail: {}", e))?;
+            if response.content_length().is_some_and(|length| length > THUMBNAIL_MAX_BYTES as u64) {
+                return Err("Thumbnail is too large".to_string());
+            }
+            
+            // The declared length can be missing or wrong, so enforce the limit while reading
+            let mut bytes = Vec::new();
+            while let Some(chunk) = response.chunk().await
+                .map_err(|e| format!("Failed to download thumbnail: {}", e))?
+            {
+                bytes.extend_from_slice(&chunk);
+                if bytes.len() > THUMBNAIL_MAX_BYTES {
+                    return Err("Thumbnail is too large".to_string());
+                }
+            }
+            
+            image_mime_type(&bytes).ok_or("Thumbnail is not a jpg or webp image")?;
+            if let Err(e) = fs::write(&cache_path, &bytes) {
+                eprintln!("Failed to cache thumbnail: {}", e);
+            }
+            bytes
+        }
+    };
+    
+    let mime_type = image_mime_type(&bytes).ok_or("Thumbnail is not a jpg or webp image")?;
+    Ok(format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    ))
+}
+
+// Helper function to get the directory where storyboard sheets are cached
+fn get_storyboards_dir(video_id: &str) -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("storyboards");
+    path.push(video_id);
+    
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    
+    Ok(path)
+}
+
+// Get a video's storyboard (preview frame sprite sheets), downloading the sheets locally
+#[tauri::command]
+pub async fn get_storyboard(url: String) -> Result<Storyboard, String> {
+    if find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to download storyboards".to_string());
+    }
+    let json = fetch_video_json_async(&url).await?;
+    
+    // Storyboards are the mhtml formats filtered out by parse_format; use the largest one
+    let format = json["formats"].as_array()
+        .ok_or("No formats found")?
+        .iter()
+        .filter(|f| {
+            f["ext"].as_str() == Some("mhtml")
+                || f["format_note"].as_str().unwrap_or("").contains("storyboard")
+        })
+        .max_by_key(|f| f["width"].as_u64().unwrap_or(0))
+        .ok_or("No storyboard available for this video")?;
+    
+    let columns = format["columns"].as_u64().unwrap_or(1).max(1) as u32;
+    let rows = format["rows"].as_u64().unwrap_or(1).max(1) as u32;
+    let fragments = format["fragments"].as_array()
+        .ok_or("Storyboard has no sprite sheets")?;
+    
+    let video_id = json["id"].as_str().unwrap_or("unknown");
+    let dir = get_storyboards_dir(video_id)?;
+    let format_id = format["format_id"].as_str().unwrap_or("sb");
+    
+    let mut sheets = Vec::new();
+    let mut start = 0.0;
+    for (index, fragment) in fragments.iter().enumerate() {
+        let fragment_url = fragment["url"].as_str().ok_or("Storyboard sheet has no URL")?;
+        let duration = fragment["duration"].as_f64().unwrap_or(0.0);
+        let sheet_path = dir.join(format!("{}-{}.jpg", format_id, index));
+        
+        if !sheet_path.exists() {
+            download_image_as_jpg(fragment_url, &sheet_path)
+                .map_err(|e| format!("Failed to download storyboard sheet: {}", e))?;
+        }
+        
+        sheets.push(StoryboardSheet {
+            path: sheet_path.to_string_lossy().to_string(),
+            start,
+            duration,
+        });
+        start += duration;
+    }
+    
+    // yt-dlp reports the storyboard frame rate; fall back to spreading a sheet's duration over its tiles
+    let frame_interval = match format["fps"].as_f64() {
+        Some(fps) if fps > 0.0 => 1.0 / fps,
+        _ => sheets.first().map(|s| s.duration / (columns * rows) as f64).unwrap_or(0.0),
+    };
+    
+    Ok(Storyboard {
+        sheets,
+        columns,
+        rows,
+        tile_width: format["width"].as_u64().unwrap_or(0) as u32,
+        tile_height: format["height"].as_u64().unwrap_or(0) as u32,
+        frame_interval,
+    })
+}
+
+// Whether a format plays on a given player/platform
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerCompatibility {
+    pub player: String,
+    pub supported: bool,
+    pub note: String,
+}
+
+// Codec support for one player/platform
+// Codec names are the normalized ones returned by normalize_codec.
+struct CodecSupport {
+    player: &'static str,
+    containers: &'static [&'static str],
+    video_codecs: &'static [&'static str],
+    audio_codecs: &'static [&'static str],
+}
+
+const CODEC_COMPATIBILITY: &[CodecSupport] = &[
+    CodecSupport {
+        player: "QuickTime",
+        containers: &["mp4", "m4a", "mov"],
+        video_codecs: &["h264", "h265"],
+        audio_codecs: &["aac", "mp3"],
+    },
+    CodecSupport {
+        player: "Windows Media Player",
+        containers: &["mp4", "m4a", "mkv"],
+        video_codecs: &["h264", "h265"],
+        audio_codecs: &["aac", "mp3"],
+    },
+    CodecSupport {
+        player: "iOS",
+        containers: &["mp4", "m4a", "mov"],
+        video_codecs: &["h264", "h265"],
+        audio_codecs: &["aac", "mp3"],
+    },
+    CodecSupport {
+        player: "Android",
+        containers: &["mp4", "m4a", "webm", "mkv"],
+        video_codecs: &["h264", "h265", "vp9", "av1"],
+        audio_codecs: &["aac", "mp3", "opus", "vorbis"],
+    },
+    CodecSupport {
+        player: "VLC",
+        containers: &["mp4", "m4a", "webm", "mkv", "mov"],
+        video_codecs: &["h264", "h265", "vp9", "av1"],
+        audio_codecs: &["aac", "mp3", "opus", "vorbis"],
+    },
+];
+
+// Helper function to map yt-dlp codec strings (e.g. "avc1.640028", "mp4a.40.2") to common names
+fn normalize_codec(codec: &str) -> String {
+    let codec = codec.to_lowercase();
+    let prefixes = [
+        ("avc", "h264"), ("h264", "h264"),
+        ("hev", "h265"), ("hvc", "h265"), ("h265", "h265"),
+        ("vp09", "vp9"), ("vp9", "vp9"),
+        ("av01", "av1"), ("av1", "av1"),
+        ("mp4a", "aac"), ("aac", "aac"),
+        ("mp3", "mp3"), ("opus", "opus"), ("vorbis", "vorbis"),
+    ];
+    prefixes
+        .iter()
+        .find(|(prefix, _)| codec.starts_with(prefix))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(codec)
+}
+
+// Check which common players can play a format's container/codec combination
+#[tauri::command]
+pub async fn check_format_compatibility(format: VideoFormat) -> Result<Vec<PlayerCompatibility>, String> {
+    let ext = format.ext.to_lowercase();
+    let vcodec = normalize_codec(&format.vcodec);
+    let acodec = normalize_codec(&format.acodec);
+    let has_video = !format.vcodec.is_empty() && vcodec != "none";
+    let has_audio = !format.acodec.is_empty() && acodec != "none";
+    
+    let results = CODEC_COMPATIBILITY
+        .iter()
+        .map(|support| {
+            let mut problems = Vec::new();
+            if !support.containers.contains(&ext.as_str()) {
+                problems.push(ext.clone());
+            }
+            if has_video && !support.video_codecs.contains(&vcodec.as_str()) {
+                problems.push(vcodec.clone());
+            }
+            if has_audio && !support.audio_codecs.contains(&acodec.as_str()) {
+                problems.push(acodec.clone());
+            }
+            
+            let note = if problems.is_empty() {
+                String::new()
+            } else {
+                format!("May not play in {} ({}) — consider mp4/H.264", support.player, problems.join(", "))
+            };
+            
+            PlayerCompatibility {
+                player: support.player.to_string(),
+                supported: problems.is_empty(),
+                note,
+            }
+        })
+        .collect();
+    
+    Ok(results)
+}
+
+// Get available subtitles
+#[tauri::command]
+pub async fn get_available_subtitles(url: String, app: AppHandle) -> Result<Vec<Subtitle>, String> {
+    // Use --dump-json to get JSON output (subtitles are included in the video info)
+    let json = fetch_video_json_checked_async(&url, &app).await?;
+
+    let subtitle_list = parse_subtitles(&json, read_settings().subtitle_mode);
+
+    Ok(subtitle_list)
+}
+
+// Helper function to list subtitles from yt-dlp JSON for the given subtitle mode.
+// Manual subtitles come from "subtitles", auto-generated ones from "automatic_captions";
+// in "both" mode a manual track wins over an auto track for the same language.
+fn parse_subtitles(json: &serde_json::Value, mode: SubtitleMode) -> Vec<Subtitle> {
+    let mut sources = Vec::new();
+    if mode != SubtitleMode::Auto {
+        sources.push(("subtitles", false));
+    }
+    if mode != SubtitleMode::Manual {
+        sources.push(("automatic_captions", true));
+    }
+    
+    let mut subtitle_list: Vec<Subtitle> = Vec::new();
+    for (field, is_auto) in sources {
+        if let Some(subtitles) = json[field].as_object() {
+            for (lang, data) in subtitles {
+                if subtitle_list.iter().any(|s| &s.lang == lang) {
+                    continue;
+                }
+                if let Some(sub_array) = data.as_array() {
+                    if let Some(first_sub) = sub_array.first() {
+                        subtitle_list.push(Subtitle {
+                            lang: lang.clone(),
+                            name: first_sub["name"].as_str().unwrap_or(lang).to_string(),
+                            format: first_sub["ext"].as_str().unwrap_or("srt").to_string(),
+                            is_auto,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    
+    subtitle_list
+}
+
+// Helper function to pick the yt-dlp subtitle language for `lang`. A manual track, or the
+// auto captions of a video spoken in `lang` ("<lang>-orig"), are original; otherwise
+// YouTube's translation of the auto captions is used.
+fn select_translated_subtitle(json: &serde_json::Value, lang: &str) -> Option<(String, SubtitleSource)> {
+    let has_track = |field: &str, key: &str| {
+        json[field][key].as_array().is_some_and(|tracks| !tracks.is_empty())
+    };
+    
+    let original = format!("{}-orig", lang);
+    if has_track("subtitles", lang) {
+        Some((lang.to_string(), SubtitleSource::Original))
+    } else if has_track("automatic_captions", &original) {
+        Some((original, SubtitleSource::Original))
+    } else if has_track("automatic_captions", lang) {
+        Some((lang.to_string(), SubtitleSource::Translated))
+    } else {
+        None
+    }
+}
+
+// Aggregate size estimate for a playlist
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistSizeEstimate {
+    pub item_count: u32,
+    pub sampled_count: u32,
+    // Sum of the sizes reported for the sampled items
+    pub sampled_bytes: u64,
+    // Sampled total extrapolated to the whole playlist
+    pub estimated_total_bytes: u64,
+    // True when every item was sampled and reported a size
+    pub is_exact: bool,
+}
+
+// Helper function to parse a time as seconds or [[hh:]mm:]ss, e.g. "90", "1:30" or "1:02:03.5"
+fn parse_clip_time(value: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid time: {} (use seconds or hh:mm:ss)", value);
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    
+    let mut seconds = 0.0;
+    for (i, part) in parts.iter().enumerate() {
+        let number = part.parse::<f64>().map_err(|_| invalid())?;
+        // Minutes and seconds after a colon stay below 60
+        if !number.is_finite() || number < 0.0 || (i > 0 && number >= 60.0) {
+            return Err(invalid());
+        }
+        seconds = seconds * 60.0 + number;
+    }
+    Ok(seconds)
+}
+
+// Helper function to build the --download-sections value for a clip, e.g. "*90-210"
+fn clip_section(start_time: Option<&str>, end_time: Option<&str>) -> Result<Option<String>, String> {
+    if start_time.is_none() && end_time.is_none() {
+        return Ok(None);
+    }
+    
+    let start = start_time.map(parse_clip_time).transpose()?.unwrap_or(0.0);
+    let end = end_time.map(parse_clip_time).transpose()?;
+    if let Some(end) = end {
+        if end <= start {
+            return Err("End time must be after the start time".to_string());
+        }
+    }
+    
+    let end = end.map(|end| end.to_string()).unwrap_or_else(|| "inf".to_string());
+    Ok(Some(format!("*{}-{}", start, end)))
+}
+
+// Helper function to check an --audio-quality value: a VBR quality 0-10 or a bitrate like "192K"
+fn validate_audio_quality(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    let valid = match value.strip_suffix(['K', 'k']) {
+        Some(bitrate) => bitrate.parse::<u32>().is_ok_and(|b| b > 0),
+        None => value.parse::<u8>().is_ok_and(|q| q <= 10),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid audio quality: {} (use 0-10 or a bitrate like 192K)", value))
+    }
+}
+
+// Helper function to check a retry count, which is a number or "infinite"
+fn validate_retries(name: &str, value: Option<&str>) -> Result<(), String> {
+    match value.map(str::trim) {
+        None | Some("infinite") => Ok(()),
+        Some(value) if value.parse::<u32>().is_ok() => Ok(()),
+        Some(value) => Err(format!("{} must be a number or \"infinite\": {}", name, value)),
+    }
+}
+
+// Helper function to check a 1-based playlist item range
+fn validate_playlist_range(start: Option<u32>, end: Option<u32>) -> Result<(), String> {
+    if start == Some(0) || end == Some(0) {
+        return Err("Playlist items are numbered from 1".to_string());
+    }
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return Err(format!("Playlist start ({}) is after playlist end ({})", start, end));
+        }
+    }
+    Ok(())
+}
+
+// Count how many playlist items a playlist_start/playlist_end range will download
+#[tauri::command]
+pub async fn get_playlist_range_count(
+    url: String,
+    playlist_start: Option<u32>,
+    playlist_end: Option<u32>,
+) -> Result<u32, String> {
+    validate_playlist_range(playlist_start, playlist_end)?;
+    
+    let entries = tauri::async_runtime::spawn_blocking(move || fetch_flat_playlist(&url))
+        .await
+        .map_err(|e| format!("Failed to fetch playlist: {}", e))??;
+    let item_count = entries.len() as u32;
+    let start = playlist_start.unwrap_or(1);
+    let end = playlist_end.unwrap_or(item_count).min(item_count);
+    
+    Ok((end + 1).saturating_sub(start))
+}
+
+// Estimate the total download size of a playlist, optionally sampling a subset of items
+#[tauri::command]
+pub async fn get_playlist_size_estimate(
+    url: String,
+    quality: Option<String>,
+    sample_size: Option<u32>,
+) -> Result<PlaylistSizeEstimate, String> {
+    tauri::async_runtime::spawn_blocking(move || estimate_playlist_size(&url, quality, sample_size))
+        .await
+        .map_err(|e| format!("Failed to estimate playlist size: {}", e))?
+}
+
+// Helper function to sample a playlist's item sizes and extrapolate the total
+fn estimate_playlist_size(
+    url: &str,
+    quality: Option<String>,
+    sample_size: Option<u32>,
+) -> Result<PlaylistSizeEstimate, String> {
+    let entries = fetch_flat_playlist(url)?;
+    let item_urls: Vec<String> = entries
+        .iter()
+        .filter_map(|e| e["url"].as_str().or(e["webpage_url"].as_str()).map(|u| u.to_string()))
+        .collect();
+    
+    let item_count = item_urls.len() as u32;
+    if item_count == 0 {
+        return Err("Playlist has no entries".to_string());
+    }
+    
+    // Pick evenly spaced items so the sample covers the whole playlist
+    let sample_count = sample_size.unwrap_or(item_count).clamp(1, item_count);
+    let sampled_urls: Vec<&str> = (0..sample_count)
+        .map(|i| item_urls[(i as usize * item_urls.len()) / sample_count as usize].as_str())
+        .collect();
+    
+    let format = match quality.or_else(|| read_settings().default_quality) {
+        Some(quality) => quality_format_string(&quality)?,
+        None => DEFAULT_FORMAT.to_string(),
+    };
+    
+    let mut args = vec![
+        "--simulate",
+        "--ignore-errors",
+        "--print", "%(filesize,filesize_approx)s",
+        "-f", format.as_str(),
+    ];
+    args.extend(sampled_urls);
+    let output = run_yt_dlp_info(&args, &read_settings().auth_method)?;
+    
+    // Unavailable items or unknown sizes print "NA" or nothing
+    let sizes: Vec<u64> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect();
+    
+    let sized_count = sizes.len() as u32;
+    let sampled_bytes: u64 = sizes.iter().sum();
+    let estimated_total_bytes = if sized_count == 0 {
+        0
+    } else {
+        (sampled_bytes as u128 * item_count as u128 / sized_count as u128) as u64
+    };
+    
+    Ok(PlaylistSizeEstimate {
+        item_count,
+        sampled_count: sample_count,
+        sampled_bytes,
+        estimated_total_bytes,
+        is_exact: sized_count == item_count,
+    })
+}
+
+// Start download
+#[tauri::command]
+pub async fn start_download(
+    options: DownloadOptions,
+    app: AppHandle,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    start_download_with_id(uuid::Uuid::new_v4().to_string(), options, app, manager).await
+}
+
+// Helper function to start a download under a given ID (the queue entry's, for queued downloads)
+async fn start_download_with_id(
+    download_id: String,
+    mut options: DownloadOptions,
+    app: AppHandle,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    // Without an output path, use the saved output template in the save location
+    if options.output.trim().is_empty() {
+        let template = read_settings().output_template
+            .unwrap_or_else(|| DEFAULT_OUTPUT_TEMPLATE.to_string());
+        options.output = output_path_for_template(&template).await?;
+    }
+    
+    let app_clone = app.clone();
+
+    if let Some(max_secs) = options.max_duration_split {
+        if max_secs <= 60 {
+            return Err("Split duration must be longer than 60 seconds".to_string());
+        }
+        if find_ffmpeg().is_none() {
+            return Err("ffmpeg is required to split downloads".to_string());
+        }
+    }
+    if let Some(audio_format) = &options.audio_format {
+        if !AUDIO_FORMATS.contains(&audio_format.as_str()) {
+            return Err(format!("Unsupported audio format: {}", audio_format));
+        }
+    }
+    if (options.audio_format.is_some() || options.audio_only) && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to extract audio".to_string());
+    }
+    if let Some(audio_quality) = &options.audio_quality {
+        validate_audio_quality(audio_quality)?;
+    }
+
+    if let Some(format_sort) = &options.format_sort {
+        validate_format_sort(format_sort)?;
+    }
+    // A single format ID only; "+" merges and "/" fallbacks would pick other streams
+    if options.exact_format {
+        let format_id = options.format.trim();
+        let valid_id = !format_id.is_empty()
+            && format_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err(format!("Invalid format ID: {}", format_id));
+        }
+    }
+    let throttle_threshold = match &options.throttled_rate {
+        Some(rate) => parse_byte_size(rate)? as f64,
+        None => DEFAULT_THROTTLE_THRESHOLD_BPS,
+    };
+    if let Some(quality) = &options.thumbnail_quality {
+        if !THUMBNAIL_QUALITIES.iter().any(|(name, _)| name == quality) {
+            return Err(format!("Unknown thumbnail quality: {}", quality));
+        }
+    }
+    if let Some(max_filesize) = &options.max_filesize {
+        parse_byte_size(max_filesize)?;
+    }
+    for arg in options.extractor_args.iter().flatten() {
+        validate_extractor_arg(arg)?;
+    }
+    validate_http_header("User-Agent", options.user_agent.as_deref())?;
+    validate_http_header("Referer", options.referer.as_deref())?;
+    validate_playlist_range(options.playlist_start, options.playlist_end)?;
+    validate_retries("Fragment retries", options.fragment_retries.as_deref())?;
+    validate_retries("Retries", options.retries.as_deref())?;
+    if let Some(lang) = &options.subtitle_translate_to {
+        if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid subtitle language: {}", lang));
+        }
+    }
+    if options.embed_subtitles && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to embed subtitles".to_string());
+    }
+    if options.burn_subtitles && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to burn in subtitles".to_string());
+    }
+    let clip_section = clip_section(options.start_time.as_deref(), options.end_time.as_deref())?;
+    if clip_section.is_some() && options.download_section.is_some() {
+        return Err("Use either a time range or a download section, not both".to_string());
+    }
+    if (options.embed_metadata || options.embed_chapters) && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to embed metadata and chapters".to_string());
+    }
+    if options.split_chapters && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to split chapters".to_string());
+    }
+    if (options.download_section.is_some() || clip_section.is_some()) && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to download part of a video".to_string());
+    }
+    if options.prefer_quality_over_compatibility && find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to merge the best video and audio streams".to_string());
+    }
+    let is_playlist = is_playlist_url(&options.url);
+    let archive_path = if options.download_archive && is_playlist {
+        Some(get_playlist_archive_path(&options.url)?)
+    } else {
+        None
+    };
+    let match_filter = match &options.match_filters {
+        Some(filters) if is_playlist => Some(build_match_filter(filters)?),
+        _ => None,
+    };
+
+    // Build yt-dlp command
+    let yt_dlp_info = find_yt_dlp_with_resources()?;
+    let settings = read_settings();
+    let mut cmd = Command::new(&yt_dlp_info.path);
+    
+    // Configure PATH and JS runtime to include bundled resources (bun)
+    configure_command_env(&mut cmd, &yt_dlp_info);
+    let mut extractor_args = options.extractor_args.clone().unwrap_or_else(|| settings.extractor_args.clone());
+    if options.extractor_args.is_none() {
+        let cached_client = options.channel_id.as_ref()
+            .and_then(|channel_id| read_player_client_cache().remove(channel_id));
+        if let Some(client) = cached_client {
+            extractor_args.push(format!("youtube:player_client={}", client));
+        }
+    }
+    apply_extractor_args(&mut cmd, &extractor_args);
+    apply_http_headers(
+        &mut cmd,
+        options.user_agent.as_deref().or(settings.user_agent.as_deref()),
+        options.referer.as_deref().or(settings.referer.as_deref()),
+    );
+    
+    // Pasted cookies are written to a private file in the app's temp dir that is
+    // removed when the download ends
+    let temp_cookies = match &options.cookies_content {
+        Some(content) => {
+            let validation = validate_cookies_content(content);
+            if !validation.is_valid {
+                return Err(format!("Invalid cookies: {}", validation.message));
+            }
+            let path = get_temp_dir()?.join(format!("cookies-{}.txt", download_id));
+            Some(TempCookiesFile::create(path, content)?)
+        }
+        None => None,
+    };
+    let download_cookies = temp_cookies.as_ref()
+        .map(|file| file.path.to_string_lossy().to_string())
+        .or_else(|| options.cookies.clone());
+    
+    // Authenticate with user-supplied cookies, OAuth2 tokens, or the cookies file
+    let applied_auth = if let Some(cookies) = &download_cookies {
+        cmd.arg("--cookies").arg(cookies);
+        "cookies"
+    } else {
+        apply_auth_args(&mut cmd, &settings.auth_method)?
+    };
+    
+    // Set ffmpeg location if found (required for merging video+audio)
+    if let Some(ffmpeg_path) = find_ffmpeg() {
+        // Get the directory containing ffmpeg
+        if let Some(ffmpeg_dir) = std::path::Path::new(&ffmpeg_path).parent() {
+            cmd.arg("--ffmpeg-location").arg(ffmpeg_dir);
+        }
+    }
+    
+    // Use best video+audio format and let yt-dlp merge them properly
+    // This avoids the MPEG-TS container issues and ensures seekable video
+    // An explicit preset wins, then the saved format chain, then the default preset.
+    // prefer_quality_over_compatibility keeps the preset's height limit but drops mp4/m4a.
+    // A selected format ID comes first, with that selection as its fallback.
+    let quality = options.quality.clone().or_else(|| {
+        settings.default_quality.clone().filter(|_| settings.format_preference.is_none())
+    });
+    let quality_format = match (&quality, &settings.format_preference) {
+        _ if options.prefer_quality_over_compatibility => best_quality_format_string(quality.as_deref())?,
+        (Some(quality), _) => quality_format_string(quality)?,
+        (None, Some(preference)) => preference.clone(),
+        (None, None) => DEFAULT_FORMAT.to_string(),
+    };
+    let format = if options.audio_only {
+        AUDIO_ONLY_FORMAT.to_string()
+    } else if options.exact_format {
+        options.format.trim().to_string()
+    } else {
+        selected_format_string(&options.format, &quality_format).unwrap_or(quality_format)
+    };
+    cmd.arg("-f").arg(&format);
+    let format_arg_index = cmd.get_args().count() - 1;
+    // mkv can hold any codec combination; mp4 keeps the default selection widely playable
+    // A single stream has nothing to merge
+    let merge_format = if options.prefer_quality_over_compatibility { "mkv" } else { "mp4" };
+    if !options.exact_format {
+        cmd.arg("--merge-output-format").arg(merge_format);
+    }
+    if let Some(format_sort) = &options.format_sort {
+        cmd.arg("-S").arg(format_sort);
+    }
+    
+    // Extract audio, optionally keeping the downloaded video too
+    let audio_format = options.audio_format.as_deref().or(options.audio_only.then_some("best"));
+    if let Some(audio_format) = audio_format {
+        cmd.arg("-x").arg("--audio-format").arg(audio_format);
+        if let Some(audio_quality) = &options.audio_quality {
+            cmd.arg("--audio-quality").arg(audio_quality.trim());
+        }
+        if options.keep_video_and_audio && !options.audio_only {
+            cmd.arg("--keep-video");
+        }
+    }
+    
+    if let Some(rate) = &options.throttled_rate {
+        cmd.arg("--throttled-rate").arg(rate);
+    }
+    
+    if let Some(section) = &options.download_section {
+        cmd.arg("--download-sections").arg(section);
+    }
+    if let Some(section) = &clip_section {
+        cmd.arg("--download-sections").arg(section);
+        cmd.arg("--force-keyframes-at-cuts");
+    }
+    
+    // Retry failed fragments and requests inside yt-dlp before the download fails
+    cmd.arg("--fragment-retries").arg(options.fragment_retries.as_deref().unwrap_or(DEFAULT_RETRIES).trim());
+    cmd.arg("--retries").arg(options.retries.as_deref().unwrap_or(DEFAULT_RETRIES).trim());
+    
+    // The single video's info, fetched once up front for the options that need it.
+    // Playlist items can't be checked ahead of the download.
+    let needs_video_json = options.subtitle_translate_to.is_some()
+        || (options.write_thumbnail && options.thumbnail_quality.is_some());
+    let video_json = if needs_video_json && !is_playlist {
+        fetch_video_json_checked_async(&options.url, &app).await.ok()
+    } else {
+        None
+    };
+    
+    // A specific thumbnail size is saved after the download; otherwise (or if it can't be
+    // looked up) yt-dlp writes its best
+    let thumbnail_url = match (&video_json, &options.thumbnail_quality) {
+        (Some(json), Some(quality)) if options.write_thumbnail => {
+            select_thumbnail(&parse_thumbnails(json), quality).map(|t| t.url.clone())
+        }
+        _ => None,
+    };
+    if options.write_thumbnail && thumbnail_url.is_none() {
+        cmd.arg("--write-thumbnail").arg("--convert-thumbnails").arg("jpg");
+    }
+    if options.embed_thumbnail {
+        cmd.arg("--embed-thumbnail");
+    }
+    if options.embed_metadata {
+        cmd.arg("--embed-metadata");
+    }
+    if options.embed_chapters {
+        cmd.arg("--embed-chapters");
+    }
+    
+    // Skip playlist entries that don't match the user's filters
+    if let Some(filter) = &match_filter {
+        cmd.arg("--match-filter").arg(filter);
+    }
+    
+    if let Some(max_filesize) = &options.max_filesize {
+        cmd.arg("--max-filesize").arg(max_filesize.trim());
+    }
+    
+    // Record downloaded items so the next run of this playlist only fetches new ones
+    if let Some(path) = &archive_path {
+        cmd.arg("--download-archive").arg(path);
+    }
+    
+    // Private or deleted playlist items shouldn't abort the rest of the playlist
+    if is_playlist {
+        cmd.arg("--ignore-errors");
+        
+        // Only download the requested range of items
+        if let Some(start) = options.playlist_start {
+            cmd.arg("--playlist-start").arg(start.to_string());
+        }
+        if let Some(end) = options.playlist_end {
+            cmd.arg("--playlist-end").arg(end.to_string());
+        }
+    }
+    
+    // yt-dlp processes can't share a limit, so approximate the global cap by giving
+    // this download an equal share alongside the ones already running. Running
+    // downloads keep the share they started with until they finish.
+    if let Some(global_limit) = settings.global_rate_limit {
+        let running = manager.active.lock().unwrap()
+            .values()
+            .filter(|d| d.state == DownloadState::Running)
+            .count() as u64;
+        let share = (global_limit / (running + 1)).max(1);
+        cmd.arg("--limit-rate").arg(share.to_string());
+    }
+    
+    let output_template = if options.dedup_by_timestamp {
+        timestamped_output_template(&options.output)
+    } else if settings.filename_collision == FilenameCollision::AutoNumber && !is_playlist {
+        let url = options.url.clone();
+        let output = options.output.clone();
+        let cookies = download_cookies.clone();
+        tauri::async_runtime::spawn_blocking(move || numbered_output_template(&url, &output, cookies.as_deref()))
+            .await
+            .map_err(|e| format!("Failed to number output file: {}", e))??
+    } else {
+        options.output.clone()
+    };
+    cmd.arg("-o").arg(&output_template);
+    cmd.args(filename_policy_args(&settings, &output_template));
+    if options.split_chapters {
+        let chapter_template = std::path::Path::new(&output_template)
+            .parent()
+            .unwrap_or(std::path::Path::new(""))
+            .join("%(title)s - %(section_number)03d %(section_title)s.%(ext)s");
+        cmd.arg("--split-chapters");
+        cmd.arg("-o").arg(format!("chapter:{}", chapter_template.to_string_lossy()));
+    }
+    cmd.arg("--continue");
+    cmd.arg("--newline");
+    cmd.arg("--progress");
+    if supports_progress_template() {
+        cmd.arg("--progress-template").arg(PROGRESS_TEMPLATE);
+    }
+    
+    // Always download subtitles automatically, manual and/or auto-generated per the
+    // download's auto_subtitles toggle, or else the settings
+    if settings.subtitle_mode != SubtitleMode::Auto || options.auto_subtitles == Some(false) {
+        cmd.arg("--write-subs");
+    }
+    let mut write_auto_subs = options.auto_subtitles
+        .unwrap_or(settings.subtitle_mode != SubtitleMode::Manual);
+    let mut sub_langs = options.subtitle_langs.clone()
+        .filter(|langs| !langs.is_empty())
+        .unwrap_or_else(|| settings.subtitle_langs.clone());
+    if sub_langs.is_empty() {
+        sub_langs.push(system_language());
+    }
+    
+    // Make sure the language to burn in is downloaded as well
+    let burn_lang = options.burn_subtitles.then(|| {
+        options.burn_subtitle_lang.clone().unwrap_or_else(|| {
+            sub_langs.iter()
+                .find(|lang| !lang.eq_ignore_ascii_case("all"))
+                .cloned()
+                .unwrap_or_else(system_language)
+        })
+    });
+    if let Some(lang) = &burn_lang {
+        if !sub_langs.contains(lang) {
+            sub_langs.push(lang.clone());
+        }
+    }
+    
+    // Subtitles in subtitle_translate_to; playlist items can't be checked up front,
+    // so yt-dlp gets the plain language code and its source is unknown
+    let mut subtitle_source = None;
+    if let Some(lang) = &options.subtitle_translate_to {
+        let selected = video_json.as_ref().and_then(|json| select_translated_subtitle(json, lang));
+        let sub_lang = match selected {
+            Some((sub_lang, source)) => {
+                subtitle_source = Some(source);
+                sub_lang
+            }
+            None => lang.clone(),
+        };
+        // Translations and "<lang>-orig" tracks only exist among the auto-generated captions
+        if subtitle_source != Some(SubtitleSource::Original) || sub_lang.ends_with("-orig") {
+            write_auto_subs = true;
+        }
+        if !sub_langs.contains(&sub_lang) {
+            sub_langs.push(sub_lang);
+        }
+    }
+    if write_auto_subs {
+        cmd.arg("--write-auto-subs");
+    }
+    
+    // "all" selects every language; live chat replays aren't subtitles
+    if sub_langs.iter().any(|lang| lang.eq_ignore_ascii_case("all")) {
+        cmd.arg("--sub-langs").arg("all,-live_chat");
+    } else {
+        cmd.arg("--sub-langs").arg(sub_langs.join(","));
+    }
+    cmd.arg("--sub-format").arg("srt/best");
+    cmd.arg("--convert-subs").arg("srt");
+    if options.embed_subtitles {
+        cmd.arg("--embed-subs");
+        // --write-subs would otherwise keep the sidecar files after embedding
+        if !options.burn_subtitles {
+            cmd.arg("--compat-options").arg("no-keep-subs");
+        }
+    }
+
+    cmd.arg(&options.url);
+    
+    // Lower presets to step down through if the requested one keeps failing
+    let mut fallback_formats: std::collections::VecDeque<(String, String)> = match &quality {
+        Some(quality) if options.quality_fallback && !is_playlist && !options.audio_only && !options.exact_format => {
+            let requested = QUALITY_PRESETS.iter().position(|p| p == quality).unwrap_or(QUALITY_PRESETS.len());
+            QUALITY_PRESETS.iter()
+                .skip(requested + 1)
+                .filter_map(|preset| {
+                    let format = if options.prefer_quality_over_compatibility {
+                        best_quality_format_string(Some(preset))
+                    } else {
+                        quality_format_string(preset)
+                    };
+                    format.ok().map(|format| (preset.to_string(), format))
+                })
+                .collect()
+        }
+        _ => std::collections::VecDeque::new(),
+    };
+    // Everything needed to run the same command again with another -f
+    let retry_program = cmd.get_program().to_os_string();
+    let retry_args: Vec<std::ffi::OsString> = cmd.get_args().map(|arg| arg.to_os_string()).collect();
+    let retry_envs: Vec<(std::ffi::OsString, Option<std::ffi::OsString>)> = cmd.get_envs()
+        .map(|(key, value)| (key.to_os_string(), value.map(|v| v.to_os_string())))
+        .collect();
+    
+    // Run yt-dlp in its own process group so cancelling also stops its ffmpeg children
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    
+    // Capture stdout and stderr so both can be written to the download log
+    let spawn_result = cmd
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+    let mut child = match spawn_result {
+        Ok(child) => child,
+        Err(e) => return Err(format!("Failed to start download: {}", e)),
+    };
+
+    // Until yt-dlp reports a destination, use the output file name as the title
+    let title = options.title.clone().unwrap_or_else(|| {
+        std::path::Path::new(&options.output)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .filter(|stem| !stem.contains("%("))
+            .unwrap_or_else(|| options.url.clone())
+    });
+    // A queued download cancelled while it was starting isn't in the active list for
+    // cancel_download to stop, so check under the queue lock before adding it
+    {
+        let queue = manager.queue.lock().unwrap();
+        if queue.iter().any(|entry| entry.id == download_id && entry.state == QueueState::Cancelled) {
+            drop(queue);
+            let _ = terminate_process(child.id());
+            let _ = child.wait();
+            return Err("Download was cancelled".to_string());
+        }
+        manager.active.lock().unwrap().insert(download_id.clone(), ActiveDownload {
+            id: download_id.clone(),
+            title: title.clone(),
+            url: options.url.clone(),
+            progress: 0.0,
+            state: DownloadState::Running,
+            pid: Some(child.id()),
+            part_path: None,
+            destinations: Vec::new(),
+        });
+    }
+    let persisted_options = options.clone();
+    update_persisted_queue(|queue| queue.push(PersistedDownload {
+        id: download_id.clone(),
+        options: persisted_options,
+        state: DownloadState::Running,
+        progress: 0.0,
+    }));
+    update_download_progress(&app, &download_id, |p| *p = new_download_progress(&download_id));
+
+    let download_id_for_task = download_id.clone();
+    let output_path = options.output.clone();
+    let max_duration_split = options.max_duration_split;
+    let keep_video_and_audio = options.keep_video_and_audio && !options.audio_only;
+    let open_on_complete = options.open_on_complete;
+    let url = options.url.clone();
+    let mut current_quality = quality.clone();
+    let mut current_title = title;
+    let post_download_command = settings.post_download_command
+        .clone()
+        .filter(|_| settings.post_download_command_enabled);
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    
+    // Time of the last line yt-dlp printed on either stream, for stall detection
+    let last_output = Arc::new(Mutex::new(std::time::SystemTime::now()));
+    
+    // Last stderr lines, reported on the queue entry if the download fails
+    let stderr_tail: Arc<Mutex<std::collections::VecDeque<String>>> = Arc::new(Mutex::new(std::collections::VecDeque::new()));
+    
+    // Forward stderr to the terminal and the download log
+    let mut stderr_log = open_download_log(&download_id);
+    let stderr_last_output = Arc::clone(&last_output);
+    let stderr_tail_for_reader = Arc::clone(&stderr_tail);
+    let stderr_app = app.clone();
+    let stderr_id = download_id.clone();
+    let stderr_handle = std::thread::spawn(move || {
+        use std::io::{BufRead, BufReader, Write};
+        
+        let mut signature_warned = false;
+        let mut summary = PlaylistSummary::default();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            *stderr_last_output.lock().unwrap() = std::time::SystemTime::now();
+            eprintln!("yt-dlp: {}", line);
+            push_stderr_line(&stderr_tail_for_reader, &line);
+            if !signature_warned {
+                if let Some(warning) = detect_signature_warning(&line) {
+                    signature_warned = true;
+                    emit_signature_warning(&stderr_app, Some(&stderr_id), &warning);
+                }
+            }
+            
+            // With --ignore-errors each unavailable playlist item is reported and skipped
+            if let Some((video_id, reason, message)) = is_playlist.then(|| parse_playlist_item_error(&line)).flatten() {
+                match reason {
+                    PlaylistSkipReason::Private => summary.skipped_private += 1,
+                    PlaylistSkipReason::Deleted => summary.skipped_deleted += 1,
+                    PlaylistSkipReason::Failed => summary.failed += 1,
+                }
+                let emit_result = stderr_app.emit("playlist-item-skipped", serde_json::json!({
+                    "id": stderr_id,
+                    "video_id": video_id,
+                    "reason": reason,
+                    "message": message
+                }));
+                if let Err(e) = emit_result {
+                    eprintln!("Failed to emit playlist-item-skipped: {}", e);
+                }
+            }
+            if let Some(log) = stderr_log.as_mut() {
+                let _ = writeln!(log, "{}", line);
+            }
+        }
+        summary
+    });
+    
+    // Report which resolution the format selection actually resolves to
+    let precheck_app = app.clone();
+    let precheck_id = download_id.clone();
+    let precheck_url = options.url.clone();
+    let requested_quality = quality.clone();
+    let auth_method = settings.auth_method.clone();
+    std::thread::spawn(move || {
+        let selected_height = probe_selected_height(&precheck_url, &format, &auth_method);
+        let emit_result = precheck_app.emit("download-started", serde_json::json!({
+            "id": precheck_id,
+            "requested_quality": requested_quality,
+            "selected_height": selected_height
+        }));
+        if let Err(e) = emit_result {
+            eprintln!("Failed to emit download-started: {}", e);
+        }
+    });
+    
+    // Emit heartbeats until the download leaves the active list, flagging long silences
+    let heartbeat_app = app.clone();
+    let heartbeat_id = download_id.clone();
+    let heartbeat_last_output = Arc::clone(&last_output);
+    let stall_timeout = std::time::Duration::from_secs(settings.stall_timeout_secs);
+    std::thread::spawn(move || {
+        let mut stall_reported = false;
+        loop {
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            let state = heartbeat_app.state::<DownloadManager>().active.lock().unwrap()
+                .get(&heartbeat_id)
+                .map(|d| d.state);
+            let Some(state) = state else {
+                break;
+            };
+            
+            let last = *heartbeat_last_output.lock().unwrap();
+            let silent_for = last.elapsed().unwrap_or_default();
+            let last_output_at = last.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            let emit_result = heartbeat_app.emit("download-heartbeat", serde_json::json!({
+                "id": heartbeat_id,
+                "last_output_at": last_output_at,
+                "silent_secs": silent_for.as_secs()
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit download-heartbeat: {}", e);
+            }
+            
+            // A paused download is silent on purpose
+            if state == DownloadState::Running && silent_for >= stall_timeout {
+                if !stall_reported {
+                    stall_reported = true;
+                    let emit_result = heartbeat_app.emit("download-stalled", serde_json::json!({
+                        "id": heartbeat_id,
+                        "last_output_at": last_output_at,
+                        "silent_secs": silent_for.as_secs()
+                    }));
+                    if let Err(e) = emit_result {
+                        eprintln!("Failed to emit download-stalled: {}", e);
+                    }
+                }
+            } else {
+                stall_reported = false;
+            }
+        }
+    });
+    
+    // Spawn a thread to monitor the download progress
+    let mut stdout_log = open_download_log(&download_id);
+    std::thread::spawn(move || {
+        use std::io::{BufRead, BufReader, Write};
+        
+        let mut stdout = stdout;
+        let mut playlist_started: Option<std::time::Instant> = None;
+        let mut audio_path: Option<String> = None;
+        let mut final_path: Option<String> = None;
+        // Playlist items processed, and how many of those the download archive skipped
+        let mut playlist_items = 0u32;
+        let mut archived_items = 0u32;
+        let mut too_large: Option<String> = None;
+        let mut chapter_files: Vec<String> = Vec::new();
+        // When the speed first dropped below the threshold, and whether that was reported
+        let mut slow_since: Option<std::time::Instant> = None;
+        let mut throttle_reported = false;
+        let mut speed_samples: std::collections::VecDeque<f64> = std::collections::VecDeque::new();
+        // Format IDs yt-dlp picked for the current item, e.g. ["137", "140"]
+        let mut requested_formats: Vec<String> = Vec::new();
+        let mut progress_saved_at = std::time::Instant::now();
+        let status = loop {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                *last_output.lock().unwrap() = std::time::SystemTime::now();
+                eprintln!("yt-dlp: {}", line); // Debug output to terminal
+                if let Some(log) = stdout_log.as_mut() {
+                    let _ = writeln!(log, "{}", line);
+                }
+            
+                // Example: [info] dQw4w9WgXcQ: Downloading 1 format(s): 137+140
+                if let Some((_, formats)) = line.strip_prefix("[info] ").and_then(|rest| rest.split_once("format(s): ")) {
+                    requested_formats = formats.trim().split('+').map(str::to_string).collect();
+                }
+                
+                if let Some(phase) = parse_download_phase(&line, &requested_formats) {
+                    let changed = app_clone.state::<DownloadManager>().progress.lock().unwrap()
+                        .get(&download_id_for_task)
+                        .is_none_or(|p| p.phase != phase);
+                    if changed {
+                        let snapshot = update_download_progress(&app_clone, &download_id_for_task, |p| p.phase = phase);
+                        if let Err(e) = app_clone.emit("download-progress", &snapshot) {
+                            eprintln!("Failed to emit progress: {}", e);
+                        }
+                    }
+                }
+                
+                // Example: [SplitChapters] Chapter 001; Destination: /Users/me/Downloads/Some Title - 001 Intro.mp4
+                if let Some((_, path)) = line.strip_prefix("[SplitChapters] ").and_then(|rest| rest.split_once("Destination: ")) {
+                    chapter_files.push(path.trim().to_string());
+                }
+                
+                // Example: [ExtractAudio] Destination: /Users/me/Downloads/Some Title.mp3
+                if let Some(path) = line.strip_prefix("[ExtractAudio] Destination: ") {
+                    audio_path = Some(path.trim().to_string());
+                }
+            
+                // The real file name can differ from the output template (e.g. after merging)
+                if let Some(path) = parse_output_path(&line) {
+                    final_path = Some(path);
+                }
+            
+                // Example: [download] File is larger than max-filesize (1073741824 bytes > 524288000 bytes). Aborting.
+                if let Some(message) = line.strip_prefix("[download] File is larger than max-filesize") {
+                    too_large = Some(format!("File is larger than max-filesize{}", message.trim_end_matches(" Aborting.")));
+                }
+            
+                // Example: [download] dQw4w9WgXcQ: has already been recorded in the archive
+                if line.starts_with("[download] ") && line.ends_with("has already been recorded in the archive") {
+                    archived_items += 1;
+                }
+            
+                // Track playlist item progress
+                if let Some((item, total)) = parse_playlist_item(&line) {
+                    playlist_items += 1;
+                    // The previous item's speeds say nothing about this one's ETA
+                    speed_samples.clear();
+                    let started = *playlist_started.get_or_insert_with(std::time::Instant::now);
+                    let completed = item.saturating_sub(1);
+                    update_playlist_progress(&app_clone, &download_id_for_task, |p| {
+                        p.completed = completed;
+                        p.total = total;
+                        p.estimated_remaining_secs = estimate_remaining_secs(started.elapsed(), completed, total);
+                    });
+                } else if let Some(title) = parse_destination_title(&line) {
+                    current_title = title.clone();
+                    if let Some(active) = app_clone.state::<DownloadManager>().active.lock().unwrap().get_mut(&download_id_for_task) {
+                        active.title = title.clone();
+                        if let Some(path) = line.strip_prefix("[download] Destination: ") {
+                            active.part_path = Some(format!("{}.part", path.trim()));
+                            active.destinations.push(path.trim().to_string());
+                        }
+                    }
+                    if playlist_started.is_some() {
+                        update_playlist_progress(&app_clone, &download_id_for_task, |p| {
+                            p.current_title = title;
+                        });
+                    }
+                }
+            
+                // Parse progress from the structured template, or the human-readable line on older yt-dlp
+                let update = if line.starts_with(PROGRESS_PREFIX) {
+                    parse_progress_template(&line)
+                } else if line.contains("[download]") && line.contains("%") {
+                    parse_progress(&line).map(|(progress, speed, eta)| ProgressUpdate {
+                        progress,
+                        speed_bps: parse_speed_bps(&speed),
+                        speed,
+                        eta,
+                        downloaded_bytes: None,
+                        total_bytes: None,
+                        fragment_index: None,
+                        fragment_count: None,
+                    })
+                } else {
+                    None
+                };
+            
+                if let Some(update) = update {
+                    if let Some(active) = app_clone.state::<DownloadManager>().active.lock().unwrap().get_mut(&download_id_for_task) {
+                        active.progress = update.progress;
+                    }
+                    let snapshot = update_download_progress(&app_clone, &download_id_for_task, |p| {
+                        p.progress = update.progress;
+                        p.speed = update.speed.clone();
+                        p.eta = update.eta.clone();
+                        p.speed_bps = update.speed_bps;
+                        p.downloaded_bytes = update.downloaded_bytes;
+                        p.total_bytes = update.total_bytes;
+                        p.fragment_index = update.fragment_index;
+                        p.fragment_count = update.fragment_count;
+                        p.downloaded = update.downloaded_bytes.map(|b| format_byte_size(b as f64)).unwrap_or_default();
+                        p.total_size = update.total_bytes.map(|b| format_byte_size(b as f64)).unwrap_or_default();
+                        if p.phase == DownloadPhase::Starting {
+                            p.phase = DownloadPhase::Downloading;
+                        }
+                    });
+                    if progress_saved_at.elapsed() >= QUEUE_PROGRESS_SAVE_INTERVAL {
+                        progress_saved_at = std::time::Instant::now();
+                        update_persisted_queue(|queue| {
+                            if let Some(download) = queue.iter_mut().find(|d| d.id == download_id_for_task) {
+                                download.progress = update.progress;
+                            }
+                        });
+                    }
+                    // yt-dlp's ETA follows the instantaneous speed; average recent samples for a steadier one
+                    if let Some(speed_bps) = update.speed_bps {
+                        speed_samples.push_back(speed_bps);
+                        if speed_samples.len() > ETA_SMOOTHING_WINDOW {
+                            speed_samples.pop_front();
+                        }
+                    }
+                    let average_speed = speed_samples.iter().sum::<f64>() / speed_samples.len().max(1) as f64;
+                    let smoothed_eta_secs = match (update.downloaded_bytes, update.total_bytes) {
+                        (Some(downloaded), Some(total)) if average_speed > 0.0 => {
+                            Some((total.saturating_sub(downloaded) as f64 / average_speed).round() as u64)
+                        }
+                        _ => None,
+                    };
+                
+                    eprintln!("Emitting progress: {}% speed={} eta={}", update.progress, update.speed, update.eta);
+                    let emit_result = app_clone.emit("download-progress", serde_json::json!({
+                        "id": download_id_for_task.clone(),
+                        "progress": update.progress,
+                        "speed": update.speed,
+                        "eta": update.eta,
+                        "downloaded_bytes": update.downloaded_bytes,
+                        "total_bytes": update.total_bytes,
+                        "speed_bps": update.speed_bps,
+                        "smoothed_eta_secs": smoothed_eta_secs,
+                        "downloaded": snapshot.downloaded,
+                        "total_size": snapshot.total_size,
+                        "phase": snapshot.phase,
+                        "fragment_index": update.fragment_index,
+                        "fragment_count": update.fragment_count
+                    }));
+                    if let Err(e) = emit_result {
+                        eprintln!("Failed to emit progress: {}", e);
+                    }
+                
+                    // Warn once per slow stretch when the speed stays low for the grace period
+                    match update.speed_bps {
+                        Some(speed_bps) if speed_bps < throttle_threshold => {
+                            let since = *slow_since.get_or_insert_with(std::time::Instant::now);
+                            if !throttle_reported && since.elapsed() >= THROTTLE_GRACE_PERIOD {
+                                throttle_reported = true;
+                                let emit_result = app_clone.emit("download-throttled", serde_json::json!({
+                                    "id": download_id_for_task.clone(),
+                                    "speed_bps": speed_bps,
+                                    "threshold_bps": throttle_threshold,
+                                    "duration_secs": since.elapsed().as_secs()
+                                }));
+                                if let Err(e) = emit_result {
+                                    eprintln!("Failed to emit download-throttled: {}", e);
+                                }
+                            }
+                        }
+                        Some(_) => {
+                            slow_since = None;
+                            throttle_reported = false;
+                        }
+                        None => {}
+                    }
+                }
+            }
+        
+            // Wait for the process to finish
+            let status = child.wait();
+            eprintln!("Download finished with status: {:?}", status);
+            if let Some(log) = stdout_log.as_mut() {
+                let _ = writeln!(log, "Download finished with status: {:?}", status);
+            }
+        
+            // Step down to the next lower preset, unless the download was cancelled
+            // (cancel_download drops it from the persisted queue) or the app is exiting
+            let failed = matches!(&status, Ok(s) if !s.success());
+            let cancelled = *app_clone.state::<DownloadManager>().shutting_down.lock().unwrap()
+                || !read_persisted_queue().iter().any(|d| d.id == download_id_for_task);
+            if !failed || cancelled || too_large.is_some() {
+                break status;
+            }
+            let Some((next_quality, next_format)) = fallback_formats.pop_front() else {
+                break status;
+            };
+        
+            let mut retry = Command::new(&retry_program);
+            for (key, value) in &retry_envs {
+                match value {
+                    Some(value) => retry.env(key, value),
+                    None => retry.env_remove(key),
+                };
+            }
+            for (i, arg) in retry_args.iter().enumerate() {
+                if i == format_arg_index {
+                    retry.arg(&next_format);
+                } else {
+                    retry.arg(arg);
+                }
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                retry.process_group(0);
+            }
+            let spawn_result = retry
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn();
+            let mut next_child = match spawn_result {
+                Ok(next_child) => next_child,
+                Err(e) => {
+                    eprintln!("Failed to retry download at {}: {}", next_quality, e);
+                    break status;
+                }
+            };
+            let (Some(next_stdout), Some(next_stderr)) = (next_child.stdout.take(), next_child.stderr.take()) else {
+                let _ = next_child.kill();
+                break status;
+            };
+        
+            // Forward the retry's stderr to the terminal and the download log
+            let mut retry_log = open_download_log(&download_id_for_task);
+            let retry_stderr_tail = Arc::clone(&stderr_tail);
+            std::thread::spawn(move || {
+                for line in BufReader::new(next_stderr).lines().map_while(Result::ok) {
+                    eprintln!("yt-dlp: {}", line);
+                    push_stderr_line(&retry_stderr_tail, &line);
+                    if let Some(log) = retry_log.as_mut() {
+                        let _ = writeln!(log, "{}", line);
+                    }
+                }
+            });
+        
+            if let Some(active) = app_clone.state::<DownloadManager>().active.lock().unwrap().get_mut(&download_id_for_task) {
+                active.pid = Some(next_child.id());
+                active.progress = 0.0;
+            }
+            update_download_progress(&app_clone, &download_id_for_task, |p| *p = new_download_progress(&download_id_for_task));
+            let emit_result = app_clone.emit("quality-downgraded", serde_json::json!({
+                "id": download_id_for_task,
+                "from": current_quality,
+                "to": next_quality
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit quality-downgraded: {}", e);
+            }
+            current_quality = Some(next_quality);
+            final_path = None;
+            chapter_files.clear();
+            child = next_child;
+            stdout = next_stdout;
+        };
+        
+        {
+            let manager = app_clone.state::<DownloadManager>();
+            manager.active.lock().unwrap().remove(&download_id_for_task);
+        }
+        // Downloads killed by an app exit stay persisted so restore_queue can resume them
+        if !*app_clone.state::<DownloadManager>().shutting_down.lock().unwrap() {
+            update_persisted_queue(|queue| queue.retain(|d| d.id != download_id_for_task));
+        }
+        
+        // Persist any access token the OAuth2 plugin refreshed during the download
+        if applied_auth == "oauth" {
+            sync_oauth_tokens();
+        }
+        
+        drop(temp_cookies);
+        
+        // Mark the final playlist item as done
+        if playlist_started.is_some() && matches!(&status, Ok(s) if s.success()) {
+            update_playlist_progress(&app_clone, &download_id_for_task, |p| {
+                p.completed = p.total;
+                p.estimated_remaining_secs = Some(0);
+            });
+        }
+        
+        // Emit completion event
+        eprintln!("Emitting download-complete for: {}", download_id_for_task);
+        // Without --keep-video the extracted audio replaces the video file
+        let video_path = final_path.unwrap_or_else(|| output_path.clone());
+        let mut files = Vec::new();
+        if too_large.is_none() && (audio_path.is_none() || keep_video_and_audio) {
+            files.push(video_path.clone());
+        }
+        files.extend(audio_path);
+        files.extend(chapter_files.iter().cloned());
+        
+        let archive = archive_path.as_ref().map(|_| serde_json::json!({
+            "skipped": archived_items,
+            "downloaded": playlist_items.saturating_sub(archived_items)
+        }));
+        // Items that were neither archived nor skipped count as downloaded
+        let playlist = stderr_handle.join().ok().filter(|_| is_playlist).map(|mut summary| {
+            summary.downloaded = playlist_items
+                .saturating_sub(archived_items)
+                .saturating_sub(summary.skipped_private + summary.skipped_deleted + summary.failed);
+            summary
+        });
+        let succeeded = too_large.is_none() && matches!(&status, Ok(s) if s.success());
+        let emit_result = app_clone.emit("download-complete", serde_json::json!({
+            "id": download_id_for_task,
+            "success": succeeded,
+            "path": files.first(),
+            "files": files,
+            "archive": archive,
+            "playlist": playlist,
+            "subtitle_source": subtitle_source,
+            "chapters": chapter_files,
+            "error": too_large.as_ref().map(|message| format!("{}: {}", FILE_TOO_LARGE_ERROR, message))
+        }));
+        if let Err(e) = emit_result {
+            eprintln!("Failed to emit download-complete: {}", e);
+        }
+        
+        let snapshot = update_download_progress(&app_clone, &download_id_for_task, |p| {
+            p.phase = if succeeded { DownloadPhase::Finished } else { DownloadPhase::Failed };
+            p.speed_bps = None;
+        });
+        if let Err(e) = app_clone.emit("download-progress", &snapshot) {
+            eprintln!("Failed to emit progress: {}", e);
+        }
+        
+        // Free the slot for the next queued download
+        let queue_error = match (&status, &too_large) {
+            (_, Some(message)) => Some(format!("{}: {}", FILE_TOO_LARGE_ERROR, message)),
+            (Ok(s), None) if s.success() => None,
+            (Ok(s), None) => Some(format!("Download failed: {}", s)),
+            (Err(e), None) => Some(format!("Download failed: {}", e)),
+        };
+        let stderr_output = queue_error.as_ref()
+            .map(|_| stderr_tail.lock().unwrap().iter().cloned().collect::<Vec<_>>().join("\n"));
+        finish_queued_download(&app_clone, &download_id_for_task, queue_error, stderr_output);
+        
+        // Nothing was written, so there is nothing to post-process
+        if too_large.is_some() {
+            return;
+        }
+        
+        // Open the finished file for the user
+        if let (true, Ok(s), Some(path)) = (open_on_complete, &status, files.first()) {
+            if s.success() {
+                let path = path.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = open_file(path).await {
+                        eprintln!("Failed to open downloaded file: {}", e);
+                    }
+                });
+            }
+        }
+        
+        // Post-processing: save the thumbnail in the requested size
+        if let (Some(thumbnail_url), Ok(s)) = (&thumbnail_url, &status) {
+            if s.success() {
+                let payload = match save_thumbnail(thumbnail_url, &video_path) {
+                    Ok(path) => serde_json::json!({ "id": download_id_for_task, "path": path }),
+                    Err(e) => serde_json::json!({ "id": download_id_for_task, "error": e }),
+                };
+                if let Err(e) = app_clone.emit("thumbnail-saved", payload) {
+                    eprintln!("Failed to emit thumbnail-saved: {}", e);
+                }
+            }
+        }
+        
+        // Post-processing: burn subtitles into a separate copy of the video
+        if let (Some(lang), Ok(s)) = (&burn_lang, &status) {
+            if s.success() {
+                let emit_result = app_clone.emit("burn-subtitles-started", serde_json::json!({
+                    "id": download_id_for_task,
+                    "message": "Burning in subtitles re-encodes the whole video and may take a while"
+                }));
+                if let Err(e) = emit_result {
+                    eprintln!("Failed to emit burn-subtitles-started: {}", e);
+                }
+                
+                let result = burn_subtitles(&app_clone, &download_id_for_task, &video_path, lang);
+                let payload = match result {
+                    Ok(path) => serde_json::json!({ "id": download_id_for_task, "path": path }),
+                    Err(e) => serde_json::json!({ "id": download_id_for_task, "error": e }),
+                };
+                if let Err(e) = app_clone.emit("burn-subtitles-complete", payload) {
+                    eprintln!("Failed to emit burn-subtitles-complete: {}", e);
+                }
+            }
+        }
+        
+        // Post-processing: split long files into equal-length parts
+        if let (Some(max_secs), Ok(s)) = (max_duration_split, &status) {
+            if s.success() {
+                let result = split_by_duration(&app_clone, &download_id_for_task, &video_path, max_secs);
+                let payload = match result {
+                    Ok(files) => serde_json::json!({ "id": download_id_for_task, "files": files }),
+                    Err(e) => serde_json::json!({ "id": download_id_for_task, "error": e }),
+                };
+                if let Err(e) = app_clone.emit("split-complete", payload) {
+                    eprintln!("Failed to emit split-complete: {}", e);
+                }
+            }
+        }
+        
+        // Post-download hook
+        if let (Some(template), Ok(s), Some(filepath)) = (&post_download_command, &status, files.first()) {
+            if s.success() {
+                let payload = match run_post_download_command(template, filepath, &current_title, &url) {
+                    Ok(result) => serde_json::json!({ "id": download_id_for_task, "result": result }),
+                    Err(e) => serde_json::json!({ "id": download_id_for_task, "error": e }),
+                };
+                if let Err(e) = app_clone.emit("post-hook-result", payload) {
+                    eprintln!("Failed to emit post-hook-result: {}", e);
+                }
+            }
+        }
+    });
+
+    Ok(download_id)
+}
+
+// Download exactly one format (e.g. a video-only or audio-only stream) as-is, without
+// merging in other streams. It runs as a regular download, so it reports progress and can
+// be paused or cancelled; the command returns the downloaded file's path once it finishes.
+#[tauri::command]
+pub async fn download_specific_format(
+    url: String,
+    format_id: String,
+    output: String,
+    app: AppHandle,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    use tauri::Listener;
+    
+    let download_id = uuid::Uuid::new_v4().to_string();
+    let options = DownloadOptions {
+        url,
+        format: format_id,
+        exact_format: true,
+        output,
+        ..Default::default()
+    };
+    
+    // Listen before starting so a quick download's completion isn't missed
+    let (sender, receiver) = tokio::sync::oneshot::channel::<serde_json::Value>();
+    let sender = Mutex::new(Some(sender));
+    let listened_id = download_id.clone();
+    let listener = app.listen("download-complete", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        if payload["id"].as_str() == Some(listened_id.as_str()) {
+            if let Some(sender) = sender.lock().unwrap().take() {
+                let _ = sender.send(payload);
+            }
+        }
+    });
+    let result = match start_download_with_id(download_id, options, app.clone(), manager).await {
+        Ok(_) => receiver.await.map_err(|e| format!("Failed to wait for download: {}", e)),
+        Err(e) => Err(e),
+    };
+    app.unlisten(listener);
+    
+    let payload = result?;
+    if payload["success"].as_bool() != Some(true) {
+        return Err(payload["error"].as_str()
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "Download failed or was cancelled".to_string()));
+    }
+    payload["path"].as_str()
+        .map(|path| path.to_string())
+        .ok_or_else(|| "yt-dlp did not report the downloaded file".to_string())
+}
+
+// Get the -o template used for downloads without an output path
+#[tauri::command]
+pub async fn get_output_template() -> Result<String, String> {
+    Ok(read_settings().output_template.unwrap_or_else(|| DEFAULT_OUTPUT_TEMPLATE.to_string()))
+}
+
+// Helper function to place an output template in the save location. Absolute templates
+// are returned unchanged.
+async fn output_path_for_template(template: &str) -> Result<String, String> {
+    Ok(PathBuf::from(get_default_save_location().await?)
+        .join(template)
+        .to_string_lossy()
+        .to_string())
+}
+
+// Helper function to check the fields used in an output template. Each %(...)s field may
+// carry yt-dlp's extras (e.g. %(upload_date>%Y)s, %(artist,uploader|Unknown)s), and
+// %(ext)s is required so files keep their extension.
+fn validate_output_template_fields(template: &str) -> Result<(), String> {
+    let field_regex = regex::Regex::new(r"%(%|\(([^)]*)\))")
+        .map_err(|e| format!("Failed to create regex: {}", e))?;
+    let name_regex = regex::Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*")
+        .map_err(|e| format!("Failed to create regex: {}", e))?;
+    
+    let mut has_ext = false;
+    for caps in field_regex.captures_iter(template) {
+        // "%%" is a literal percent sign
+        let Some(expression) = caps.get(2) else {
+            continue;
+        };
+        // Drop the date format, default, and replacement parts; keep the alternatives
+        let fields = expression.as_str().split(['>', '|', '&']).next().unwrap_or("");
+        for field in fields.split(',') {
+            let name = name_regex.find(field.trim())
+                .map(|m| m.as_str())
+                .ok_or_else(|| format!("Invalid output template field: %({})s", expression.as_str()))?;
+            if !OUTPUT_TEMPLATE_FIELDS.contains(&name) {
+                return Err(format!("Unknown output template field: {}", name));
+            }
+            has_ext |= name == "ext";
+        }
+    }
+    
+    if !has_ext {
+        return Err("Output template must include %(ext)s".to_string());
+    }
+    Ok(())
+}
+
+// Helper function to get the file name yt-dlp would write for a URL with the given -o
+// template, without downloading anything. A download's own cookies file, if given, is
+// used in place of the app's authentication.
+fn render_output_name(url: &str, template: &str, cookies: Option<&str>) -> Result<String, String> {
+    let settings = read_settings();
+    let policy_args = filename_policy_args(&settings, template);
+    let mut args = vec![
+        "--simulate",
+        "--no-playlist",
+        "--no-warnings",
+        "--print", "filename",
+        "-o", template,
+    ];
+    args.extend(policy_args.iter().map(|arg| arg.as_str()));
+    // yt-dlp uses the last --cookies given, so this overrides the app's cookies file
+    let auth_method = match cookies {
+        Some(cookies) => {
+            args.extend(["--cookies", cookies]);
+            "cookies"
+        }
+        None => settings.auth_method.as_str(),
+    };
+    args.push(url);
+    let output = run_yt_dlp_info(&args, auth_method)?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Invalid output template: {}", stderr.trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let name = stdout.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("").trim();
+    if name.is_empty() {
+        return Err("Output template produces an empty file name".to_string());
+    }
+    Ok(name.to_string())
+}
+
+// Set the -o template used for downloads without an output path, checking its fields
+// and doing a dry run that prints the resulting file name. If the dry-run video can't be
+// reached, the field check alone decides.
+#[tauri::command]
+pub async fn set_output_template(template: String) -> Result<(), String> {
+    let template = template.trim().to_string();
+    if template.is_empty() {
+        return Err("Output template is empty".to_string());
+    }
+    validate_output_template_fields(&template)?;
+    let dry_run_template = template.clone();
+    let rendered = tauri::async_runtime::spawn_blocking(move || render_output_name(DRY_RUN_TEST_URL, &dry_run_template, None))
+        .await
+        .map_err(|e| format!("Failed to check output template: {}", e))?;
+    match rendered {
+        Ok(_) => {}
+        Err(e) if is_unverifiable_dry_run_error(&e) => {
+            eprintln!("Couldn't verify output template, saving it anyway: {}", e);
+        }
+        Err(e) => return Err(e),
+    }
+    
+    let mut settings = read_settings();
+    settings.output_template = Some(template);
+    write_settings(&settings)
+}
+
+// Show the full path a download of the URL would be saved to with the given output
+// template (or the saved one), so it can be checked before downloading
+#[tauri::command]
+pub async fn preview_output_name(url: String, template: Option<String>) -> Result<String, String> {
+    validate_url(url.clone())?;
+    let template = template
+        .map(|template| template.trim().to_string())
+        .filter(|template| !template.is_empty())
+        .or_else(|| read_settings().output_template)
+        .unwrap_or_else(|| DEFAULT_OUTPUT_TEMPLATE.to_string());
+    validate_output_template_fields(&template)?;
+    
+    let output_template = output_path_for_template(&template).await?;
+    tauri::async_runtime::spawn_blocking(move || render_output_name(&url, &output_template, None))
+        .await
+        .map_err(|e| format!("Failed to preview output name: {}", e))?
+}
+
+// Restore the built-in output template
+#[tauri::command]
+pub async fn reset_output_template() -> Result<(), String> {
+    let mut settings = read_settings();
+    settings.output_template = None;
+    write_settings(&settings)
+}
+
+// Helper function to add yt-dlp's extraction time to an output template, before the extension.
+// Example: "~/Videos/%(title)s.%(ext)s" -> "~/Videos/%(title)s-%(epoch>%Y%m%d-%H%M%S)s.%(ext)s"
+fn timestamped_output_template(output: &str) -> String {
+    output_template_with_suffix(output, "-%(epoch>%Y%m%d-%H%M%S)s")
+}
+
+// Helper function to add text to the end of an output template's file name, before the extension
+fn output_template_with_suffix(output: &str, suffix: &str) -> String {
+    if let Some(index) = output.rfind(".%(ext)s") {
+        return format!("{}{}{}", &output[..index], suffix, &output[index..]);
+    }
+    
+    let path = std::path::Path::new(output);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => path
+            .with_file_name(format!("{}{}.{}", stem.to_string_lossy(), suffix, ext.to_string_lossy()))
+            .to_string_lossy()
+            .to_string(),
+        _ => format!("{}{}", output, suffix),
+    }
+}
+
+// Helper function to number an output template past any file already using its name:
+// "name.mp4" exists -> "name (1).%(ext)s", and so on. Files are compared by name without
+// extension, since the container can change when streams are merged. The name is worked
+// out with the download's own cookies, if any, and failing to work it out is an error
+// rather than a risk of overwriting.
+fn numbered_output_template(url: &str, output: &str, cookies: Option<&str>) -> Result<String, String> {
+    let rendered = render_output_name(url, output, cookies)
+        .map_err(|e| format!("Failed to work out the output file name: {}", e))?;
+    let rendered = std::path::Path::new(&rendered);
+    let (Some(parent), Some(stem)) = (rendered.parent(), rendered.file_stem()) else {
+        return Err(format!("Failed to work out the output file name: {}", rendered.display()));
+    };
+    let stem = stem.to_string_lossy().to_string();
+    
+    let taken: HashSet<String> = fs::read_dir(parent)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.path().file_stem().map(|s| s.to_string_lossy().to_string()))
+            .collect())
+        .unwrap_or_default();
+    if !taken.contains(&stem) {
+        return Ok(output.to_string());
+    }
+    
+    let number = (1..).find(|n| !taken.contains(&format!("{} ({})", stem, n))).unwrap_or(1);
+    Ok(output_template_with_suffix(output, &format!(" ({})", number)))
+}
+
+// Helper function to build the file name arguments for a download to the given -o template:
+// portable names, a length limit that keeps the full path within MAX_PATH_LENGTH, and the
+// collision policy. Auto-numbering picks a free name beforehand (see numbered_output_template),
+// so it only needs yt-dlp to leave existing files alone.
+fn filename_policy_args(settings: &AppSettings, output: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    if settings.portable_filenames {
+        args.push("--windows-filenames".to_string());
+    }
+    
+    // The fixed directory part of the template; fields may add more but can't be measured
+    let directory_length = std::path::Path::new(output)
+        .parent()
+        .map(|parent| parent.to_string_lossy().split("%(").next().unwrap_or("").chars().count())
+        .unwrap_or(0);
+    let path_budget = MAX_PATH_LENGTH
+        .saturating_sub(directory_length + 1 + FILENAME_SUFFIX_ALLOWANCE)
+        .max(FILENAME_SUFFIX_ALLOWANCE);
+    let max_length = match settings.max_filename_length {
+        0 => path_budget,
+        length => length.min(path_budget),
+    };
+    args.push("--trim-filenames".to_string());
+    args.push(max_length.to_string());
+    
+    // --force-overwrites also restarts partial downloads instead of continuing them
+    args.push(match settings.filename_collision {
+        FilenameCollision::Overwrite => "--force-overwrites",
+        FilenameCollision::AutoNumber | FilenameCollision::Skip => "--no-overwrites",
+    }.to_string());
+    args
+}
+
+// Outcome of extracting a video with one player client
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerClientResult {
+    pub client: String,
+    pub success: bool,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+// Which player clients can extract a video, fastest working client first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerClientReport {
+    pub results: Vec<PlayerClientResult>,
+    pub best: Option<String>,
+    pub channel_id: Option<String>,
+}
+
+// Try extracting a URL with each YouTube player client and report which work and how fast.
+// The fastest working client is remembered for the video's channel (see DownloadOptions.channel_id).
+#[tauri::command]
+pub async fn find_working_client(url: String) -> Result<PlayerClientReport, String> {
+    let auth_method = read_settings().auth_method;
+    let mut results = Vec::new();
+    let mut channel_id = None;
+    
+    for client in PLAYER_CLIENTS {
+        let extractor_arg = format!("youtube:player_client={}", client);
+        let started = std::time::Instant::now();
+        let output = run_yt_dlp_info_async(&[
+            "--simulate",
+            "--no-playlist",
+            "--print", "%(channel_id)s",
+            "--extractor-args", &extractor_arg,
+            &url
+        ], &auth_method).await?;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        
+        let success = output.status.success();
+        if success && channel_id.is_none() {
+            channel_id = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.trim().to_string())
+                .filter(|id| !id.is_empty() && id != "NA");
+        }
+        let error = (!success).then(|| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            stderr.lines().rfind(|line| line.starts_with("ERROR")).unwrap_or("").trim().to_string()
+        });
+        
+        results.push(PlayerClientResult {
+            client: client.to_string(),
+            success,
+            elapsed_ms,
+            error,
+        });
+    }
+    
+    results.sort_by_key(|r| (!r.success, r.elapsed_ms));
+    let best = results.first().filter(|r| r.success).map(|r| r.client.clone());
+    
+    if let (Some(best), Some(channel_id)) = (&best, &channel_id) {
+        let mut cache = read_player_client_cache();
+        cache.insert(channel_id.clone(), best.clone());
+        let result = get_player_clients_path().and_then(|path| {
+            let json = serde_json::to_string_pretty(&cache)
+                .map_err(|e| format!("Failed to serialize player clients: {}", e))?;
+            fs::write(&path, json).map_err(|e| format!("Failed to write player clients: {}", e))
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to cache player client: {}", e);
+        }
+    }
+    
+    Ok(PlayerClientReport { results, best, channel_id })
+}
+
+// Helper function to build a format string for a format ID picked by the user, or None
+// when `format` is a container name (or empty) rather than an ID. A video-only ID gets
+// the best audio merged in, and `fallback` is used when the ID isn't available.
+fn selected_format_string(format: &str, fallback: &str) -> Option<String> {
+    let format = format.trim();
+    let containers = ["", "best", "mp4", "webm", "mkv", "mov", "m4a", "mp3", "opus", "flac", "wav"];
+    if containers.contains(&format.to_lowercase().as_str()) {
+        return None;
+    }
+    let is_id = format.split('+').all(|id| {
+        !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    });
+    if !is_id {
+        return None;
+    }
+    
+    if format.contains('+') {
+        Some(format!("{}/{}", format, fallback))
+    } else {
+        Some(format!("{id}[acodec!=none]/{id}+bestaudio/{}", fallback, id = format))
+    }
+}
+
+// Helper function to build a yt-dlp format string for a quality preset.
+// Uses height<=X with fallbacks so a missing exact resolution picks the next best one.
+fn quality_format_string(quality: &str) -> Result<String, String> {
+    if !QUALITY_PRESETS.contains(&quality) {
+        return Err(format!("Unknown quality preset: {}", quality));
+    }
+    
+    let Some(height) = quality.strip_suffix('p') else {
+        return Ok(DEFAULT_FORMAT.to_string());
+    };
+    
+    Ok(format!(
+        "bestvideo[height<={h}][ext=mp4]+bestaudio[ext=m4a]/best[height<={h}][ext=mp4]/best[height<={h}]/best",
+        h = height
+    ))
+}
+
+// Helper function to build a format string for prefer_quality_over_compatibility.
+// Same height limits as the quality presets, but without the mp4/m4a restriction.
+fn best_quality_format_string(quality: Option<&str>) -> Result<String, String> {
+    let Some(quality) = quality else {
+        return Ok(BEST_QUALITY_FORMAT.to_string());
+    };
+    if !QUALITY_PRESETS.contains(&quality) {
+        return Err(format!("Unknown quality preset: {}", quality));
+    }
+    
+    let Some(height) = quality.strip_suffix('p') else {
+        return Ok(BEST_QUALITY_FORMAT.to_string());
+    };
+    
+    Ok(format!("bestvideo[height<={h}]+bestaudio/best[height<={h}]/best", h = height))
+}
+
+// Helper function to ask yt-dlp which height a format selection resolves to
+fn probe_selected_height(url: &str, format: &str, auth_method: &str) -> Option<u64> {
+    let output = run_yt_dlp_info(&[
+        "--simulate",
+        "--no-playlist",
+        "--print", "%(height)s",
+        "-f", format,
+        url
+    ], auth_method).ok()?;
+    
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// Helper function to get the directory holding per-download logs
+fn get_logs_dir() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("logs");
+    
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    
+    Ok(path)
+}
+
+// Helper function to get the app's own directory for temporary files
+fn get_temp_dir() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("tmp");
+    
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    
+    Ok(path)
+}
+
+// Helper function to open (append) the log file for a download
+fn open_download_log(id: &str) -> Option<fs::File> {
+    let path = get_logs_dir().ok()?.join(format!("{}.log", id));
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| eprintln!("Failed to open download log: {}", e))
+        .ok()
+}
+
+// Get the yt-dlp output captured for a download
+#[tauri::command]
+pub async fn get_download_log(id: String) -> Result<String, String> {
+    // IDs are UUIDs; reject anything that could escape the logs directory
+    if id.contains(['/', '\\', '.']) {
+        return Err("Invalid download ID".to_string());
+    }
+    
+    let path = get_logs_dir()?.join(format!("{}.log", id));
+    fs::read_to_string(&path).map_err(|_| "No log found for this download".to_string())
+}
+
+// Open the folder containing download logs
+#[tauri::command]
+pub async fn open_log_folder() -> Result<(), String> {
+    let path = get_logs_dir()?;
+    open_file(path.to_string_lossy().to_string()).await
+}
+
+// Helper function to queue a download of a URL into the default save location using the
+// default options and output template
+async fn start_default_download(url: String, app: AppHandle) -> Result<String, String> {
+    let options = DownloadOptions {
+        url,
+        ..Default::default()
+    };
+    
+    enqueue_download(options, app).await
+}
+
+// Helper function to emit the current download queue
+fn emit_queue_updated(app: &AppHandle) {
+    let queue = app.state::<DownloadManager>().queue.lock().unwrap().clone();
+    if let Err(e) = app.emit("queue-updated", &queue) {
+        eprintln!("Failed to emit queue-updated: {}", e);
+    }
+}
+
+// Helper function to start pending queue entries while there are free download slots
+fn dispatch_queue(app: &AppHandle) {
+    let manager = app.state::<DownloadManager>();
+    if *manager.queue_paused.lock().unwrap() || *manager.shutting_down.lock().unwrap() {
+        return;
+    }
+    let max_concurrent = read_settings().max_concurrent_downloads.max(1);
+    
+    let to_start: Vec<(String, DownloadOptions)> = {
+        let mut queue = manager.queue.lock().unwrap();
+        // Paused downloads keep their slot. Entries that are starting aren't in the
+        // active list yet, so count them separately.
+        let mut busy: HashSet<String> = manager.active.lock().unwrap().keys().cloned().collect();
+        busy.extend(queue.iter().filter(|entry| entry.state == QueueState::Active).map(|entry| entry.id.clone()));
+        
+        let free_slots = max_concurrent.saturating_sub(busy.len());
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        queue.iter_mut()
+            .filter(|entry| entry.state == QueueState::Pending && entry.retry_at.is_none_or(|at| at <= now))
+            .take(free_slots)
+            .map(|entry| {
+                entry.state = QueueState::Active;
+                entry.retry_at = None;
+                (entry.id.clone(), entry.options.clone())
+            })
+            .collect()
+    };
+    if to_start.is_empty() {
+        return;
+    }
+    emit_queue_updated(app);
+    
+    for (id, options) in to_start {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            // start_download persists the entry again as running
+            update_persisted_queue(|queue| queue.retain(|d| d.id != id));
+            if let Err(e) = start_download_with_id(id.clone(), options, app.clone(), app.state()).await {
+                finish_queued_download(&app, &id, Some(e), None);
+            }
+        });
+    }
+}
+
+// Helper function to record how an active queue entry ended and start the next one.
+// Transient failures are queued again after a backoff until download_retry_attempts
+// is used up. Downloads that weren't queued are ignored, but still free a slot.
+fn finish_queued_download(app: &AppHandle, id: &str, error: Option<String>, stderr: Option<String>) {
+    let settings = read_settings();
+    let retry = {
+        let manager = app.state::<DownloadManager>();
+        let shutting_down = *manager.shutting_down.lock().unwrap();
+        let mut queue = manager.queue.lock().unwrap();
+        let Some(entry) = queue.iter_mut().find(|entry| entry.id == id && entry.state == QueueState::Active) else {
+            drop(queue);
+            dispatch_queue(app);
+            return;
+        };
+        
+        let transient = error.is_some()
+            && is_transient_download_error(&format!("{}\n{}", error.as_deref().unwrap_or(""), stderr.as_deref().unwrap_or("")));
+        let retry = if transient && !shutting_down && entry.retries < settings.download_retry_attempts {
+            let delay = settings.download_retry_backoff_secs.saturating_mul(1 << entry.retries.min(16));
+            entry.retries += 1;
+            entry.state = QueueState::Pending;
+            entry.retry_at = Some(std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+                .saturating_add(delay));
+            Some((entry.options.clone(), entry.retries, delay))
+        } else {
+            entry.state = if error.is_some() { QueueState::Failed } else { QueueState::Completed };
+            None
+        };
+        entry.error = error;
+        entry.stderr = stderr;
+        retry
+    };
+    
+    if let Some((options, attempt, delay)) = retry {
+        // Keep the retry across a restart; --continue picks up the partial file
+        update_persisted_queue(|persisted| {
+            persisted.retain(|d| d.id != id);
+            persisted.push(PersistedDownload {
+                id: id.to_string(),
+                options,
+                state: DownloadState::Queued,
+                progress: 0.0,
+            });
+        });
+        let emit_result = app.emit("download-retrying", serde_json::json!({
+            "id": id,
+            "attempt": attempt,
+            "max_attempts": settings.download_retry_attempts,
+            "delay_secs": delay
+        }));
+        if let Err(e) = emit_result {
+            eprintln!("Failed to emit download-retrying: {}", e);
+        }
+        
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs(delay));
+            dispatch_queue(&app);
+        });
+    }
+    emit_queue_updated(app);
+    dispatch_queue(app);
+}
+
+// Helper function to check whether a failed download is worth retrying automatically:
+// throttling, expiring URLs and dropped connections usually clear up on their own
+fn is_transient_download_error(output: &str) -> bool {
+    let transient_patterns = [
+        "HTTP Error 403",
+        "HTTP Error 429",
+        "HTTP Error 500",
+        "HTTP Error 502",
+        "HTTP Error 503",
+        "HTTP Error 504",
+        "Too Many Requests",
+        "Connection reset",
+        "Connection refused",
+        "Connection aborted",
+        "timed out",
+        "Temporary failure in name resolution",
+        "Network is unreachable",
+        "IncompleteRead",
+        "Got error: ",
+        "Unable to download video data",
+    ];
+    
+    let output_lower = output.to_lowercase();
+    transient_patterns.iter().any(|pattern| output_lower.contains(&pattern.to_lowercase()))
+}
+
+// Helper function to remember a stderr line, keeping only the last FAILED_STDERR_LINES
+fn push_stderr_line(tail: &Mutex<std::collections::VecDeque<String>>, line: &str) {
+    let mut tail = tail.lock().unwrap();
+    if tail.len() == FAILED_STDERR_LINES {
+        tail.pop_front();
+    }
+    tail.push_back(line.to_string());
+}
+
+// Add a download to the queue; it starts once fewer than max_concurrent_downloads are
+// running. Returns the ID its download events will use.
+#[tauri::command]
+pub async fn enqueue_download(options: DownloadOptions, app: AppHandle) -> Result<String, String> {
+    validate_url(options.url.clone())?;
+    
+    let id = uuid::Uuid::new_v4().to_string();
+    add_queue_entry(&app, &id, options, 0.0);
+    Ok(id)
+}
+
+// Helper function to add a pending queue entry under the given ID, persisted as queued so
+// it is restored after a restart. Does nothing if the ID is already waiting or running.
+fn add_queue_entry(app: &AppHandle, id: &str, options: DownloadOptions, progress: f64) {
+    {
+        let manager = app.state::<DownloadManager>();
+        let mut queue = manager.queue.lock().unwrap();
+        let in_queue = queue.iter()
+            .any(|entry| entry.id == id && matches!(entry.state, QueueState::Pending | QueueState::Active));
+        if in_queue || manager.active.lock().unwrap().contains_key(id) {
+            return;
+        }
+        
+        update_persisted_queue(|persisted| {
+            persisted.retain(|d| d.id != id);
+            persisted.push(PersistedDownload {
+                id: id.to_string(),
+                options: options.clone(),
+                state: DownloadState::Queued,
+                progress,
+            });
+        });
+        queue.retain(|entry| entry.id != id);
+        queue.push(QueueEntry {
+            id: id.to_string(),
+            options,
+            state: QueueState::Pending,
+            error: None,
+            stderr: None,
+            retries: 0,
+            retry_at: None,
+        });
+    }
+    
+    emit_queue_updated(app);
+    dispatch_queue(app);
+}
+
+// Get every entry in the download queue, including finished ones
+#[tauri::command]
+pub async fn get_queue(manager: State<'_, DownloadManager>) -> Result<Vec<QueueEntry>, String> {
+    Ok(manager.queue.lock().unwrap().clone())
+}
+
+// Queue a failed or cancelled download again under the same ID. yt-dlp continues from
+// any partial file it left behind.
+#[tauri::command]
+pub async fn retry_download(id: String, app: AppHandle) -> Result<(), String> {
+    let options = {
+        let manager = app.state::<DownloadManager>();
+        let queue = manager.queue.lock().unwrap();
+        let entry = queue.iter().find(|entry| entry.id == id).ok_or("Download not found in queue")?;
+        if !matches!(entry.state, QueueState::Failed | QueueState::Cancelled) {
+            return Err("Only failed or cancelled downloads can be retried".to_string());
+        }
+        entry.options.clone()
+    };
+    
+    add_queue_entry(&app, &id, options, 0.0);
+    Ok(())
+}
+
+// Set how many downloads may run at once, starting queued ones if there is now room
+#[tauri::command]
+pub async fn set_max_concurrent(max: usize, app: AppHandle) -> Result<(), String> {
+    if max == 0 {
+        return Err("At least one concurrent download is required".to_string());
+    }
+    
+    let mut settings = read_settings();
+    settings.max_concurrent_downloads = max;
+    write_settings(&settings)?;
+    
+    dispatch_queue(&app);
+    Ok(())
+}
+
+// Held while scheduled.json is read and written back, so the scheduler and the
+// commands don't overwrite each other's changes
+static SCHEDULED_DOWNLOADS_LOCK: Mutex<()> = Mutex::new(());
+
+// Helper function to get the path of the scheduled downloads file
+fn get_scheduled_downloads_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("scheduled.json");
+    
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    Ok(path)
+}
+
+// Helper function to read the scheduled downloads (empty if missing or invalid)
+fn read_scheduled_downloads() -> Vec<ScheduledDownload> {
+    get_scheduled_downloads_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Helper function to write the scheduled downloads back to disk
+fn write_scheduled_downloads(scheduled: &[ScheduledDownload]) -> Result<(), String> {
+    let path = get_scheduled_downloads_path()?;
+    let scheduled: Vec<ScheduledDownload> = scheduled.iter()
+        .map(|download| ScheduledDownload {
+            options: download.options.persistable(),
+            ..download.clone()
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&scheduled)
+        .map_err(|e| format!("Failed to serialize scheduled downloads: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write scheduled downloads: {}", e))
+}
+
+// Schedule a download to be queued at start_time (Unix seconds). Returns the schedule ID.
+#[tauri::command]
+pub async fn schedule_download(options: DownloadOptions, start_time: u64) -> Result<String, String> {
+    validate_url(options.url.clone())?;
+    // Cookies are never written to disk, so a scheduled run would start without them
+    if options.cookies_content.is_some() {
+        return Err("Downloads that use pasted cookies cannot be scheduled; save the cookies or sign in with OAuth instead".to_string());
+    }
+    
+    let id = uuid::Uuid::new_v4().to_string();
+    let _guard = SCHEDULED_DOWNLOADS_LOCK.lock().unwrap();
+    let mut scheduled = read_scheduled_downloads();
+    scheduled.push(ScheduledDownload {
+        id: id.clone(),
+        options,
+        start_time,
+    });
+    write_scheduled_downloads(&scheduled)?;
+    Ok(id)
+}
+
+// Get the downloads still waiting for their start time
+#[tauri::command]
+pub async fn get_scheduled_downloads() -> Result<Vec<ScheduledDownload>, String> {
+    Ok(read_scheduled_downloads())
+}
+
+// Remove a scheduled download before it starts
+#[tauri::command]
+pub async fn cancel_scheduled_download(id: String) -> Result<(), String> {
+    let _guard = SCHEDULED_DOWNLOADS_LOCK.lock().unwrap();
+    let mut scheduled = read_scheduled_downloads();
+    let count = scheduled.len();
+    scheduled.retain(|d| d.id != id);
+    if scheduled.len() == count {
+        return Err(format!("Scheduled download not found: {}", id));
+    }
+    write_scheduled_downloads(&scheduled)
+}
+
+// Start the background task that moves scheduled downloads into the download queue once
+// their start time has passed. Downloads due while the app was closed are queued at startup.
+// Called from setup in main.rs.
+pub fn start_download_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        loop {
+            if *app.state::<DownloadManager>().shutting_down.lock().unwrap() {
+                break;
+            }
+            
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let due: Vec<ScheduledDownload> = {
+                let _guard = SCHEDULED_DOWNLOADS_LOCK.lock().unwrap();
+                let (due, waiting): (Vec<ScheduledDownload>, Vec<ScheduledDownload>) = read_scheduled_downloads()
+                    .into_iter()
+                    .partition(|d| d.start_time <= now);
+                // Remove them first so a failed enqueue isn't retried on every check
+                if !due.is_empty() {
+                    if let Err(e) = write_scheduled_downloads(&waiting) {
+                        eprintln!("Failed to update scheduled downloads: {}", e);
+                    }
+                }
+                due
+            };
+            
+            for download in due {
+                let result = tauri::async_runtime::block_on(
+                    enqueue_download(download.options, app.clone())
+                );
+                let payload = match result {
+                    Ok(download_id) => serde_json::json!({ "id": download.id, "download_id": download_id }),
+                    Err(e) => serde_json::json!({ "id": download.id, "error": e }),
+                };
+                if let Err(e) = app.emit("scheduled-download-started", payload) {
+                    eprintln!("Failed to emit scheduled-download-started: {}", e);
+                }
+            }
+            
+            std::thread::sleep(std::time::Duration::from_secs(30));
+        }
+    });
+}
+
+// Helper function to read the valid video URLs from a watch list file
+fn read_watch_list_urls(path: &std::path::Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && validate_url(line.clone()).is_ok())
+        .collect()
+}
+
+// Watch a text file of URLs (one per line) and download new URLs as they are appended
+#[tauri::command]
+pub async fn start_watch_list(path: String, app: AppHandle, manager: State<'_, DownloadManager>) -> Result<(), String> {
+    use notify::Watcher;
+    
+    let watch_path = fs::canonicalize(&path)
+        .map_err(|e| format!("Failed to open watch list: {}", e))?;
+    let parent = watch_path.parent().ok_or("Invalid watch list path")?.to_path_buf();
+    
+    // URLs already in the file when watching starts are not downloaded
+    let seen: Mutex<HashSet<String>> = Mutex::new(read_watch_list_urls(&watch_path).into_iter().collect());
+    
+    let event_path = watch_path.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        // Watch the parent directory since editors often replace the file on save
+        let Ok(event) = res else {
+            return;
+        };
+        if !event.paths.iter().any(|p| p == &event_path) {
+            return;
+        }
+        
+        let new_urls: Vec<String> = {
+            let mut seen = seen.lock().unwrap();
+            read_watch_list_urls(&event_path)
+                .into_iter()
+                .filter(|url| seen.insert(url.clone()))
+                .collect()
+        };
+        
+        for url in new_urls {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = start_default_download(url.clone(), app.clone()).await;
+                let payload = match result {
+                    Ok(id) => serde_json::json!({ "url": url, "id": id }),
+                    Err(e) => serde_json::json!({ "url": url, "error": e }),
+                };
+                if let Err(e) = app.emit("watch-list-item", payload) {
+                    eprintln!("Failed to emit watch-list-item: {}", e);
+                }
+            });
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    
+    watcher
+        .watch(&parent, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch file: {}", e))?;
+    
+    // Replacing an existing watcher stops the previous watch list
+    *manager.watch_list.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+// Stop watching the URL watch list file
+#[tauri::command]
+pub async fn stop_watch_list(manager: State<'_, DownloadManager>) -> Result<(), String> {
+    manager.watch_list.lock().unwrap().take();
+    Ok(())
+}
+
+// Helper function to split a command line into arguments, honoring single and double quotes
+fn split_command_line(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    
+    if quote.is_some() {
+        return Err("Unterminated quote in command".to_string());
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+// Exit status and output of a post-download command
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PostHookResult {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// Helper function to run the post-download command with tokens substituted per argument.
+// No shell is involved, so file names can't inject extra commands.
+fn run_post_download_command(template: &str, filepath: &str, title: &str, url: &str) -> Result<PostHookResult, String> {
+    let args: Vec<String> = split_command_line(template)?
+        .into_iter()
+        .map(|arg| {
+            arg.replace("{filepath}", filepath)
+                .replace("{title}", title)
+                .replace("{url}", url)
+        })
+        .collect();
+    
+    let (program, program_args) = args.split_first().ok_or("Post-download command is empty")?;
+    let output = Command::new(program)
+        .args(program_args)
+        .output()
+        .map_err(|e| format!("Failed to run post-download command: {}", e))?;
+    
+    Ok(PostHookResult {
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+// Helper function to parse progress from yt-dlp output
+fn parse_progress(line: &str) -> Option<(f64, String, String)> {
+    // Example: [download]  45.2% of 100.00MiB at 5.00MiB/s ETA 00:10
+    let progress_regex = regex::Regex::new(r"(\d+\.?\d*)%.*?at\s+(\S+).*?ETA\s+(\S+)").ok()?;
+    
+    if let Some(caps) = progress_regex.captures(line) {
+        let progress: f64 = caps.get(1)?.as_str().parse().ok()?;
+        let speed = caps.get(2)?.as_str().to_string();
+        let eta = caps.get(3)?.as_str().to_string();
+        return Some((progress, speed, eta));
+    }
+    
+    // Simpler fallback: just get percentage
+    let simple_regex = regex::Regex::new(r"(\d+\.?\d*)%").ok()?;
+    if let Some(caps) = simple_regex.captures(line) {
+        let progress: f64 = caps.get(1)?.as_str().parse().ok()?;
+        return Some((progress, "".to_string(), "".to_string()));
+    }
+    
+    None
+}
+
+// Download progress parsed from a yt-dlp output line
+struct ProgressUpdate {
+    progress: f64,
+    // Human-readable speed and ETA, e.g. "5.00MiB/s" and "00:10"
+    speed: String,
+    eta: String,
+    speed_bps: Option<f64>,
+    downloaded_bytes: Option<u64>,
+    total_bytes: Option<u64>,
+    // Fragments done and in total, for HLS/DASH downloads
+    fragment_index: Option<u64>,
+    fragment_count: Option<u64>,
+}
+
+// One PROGRESS_TEMPLATE line: the fields of yt-dlp's progress dict this app uses.
+// Unknown values are null; numbers may be floats.
+#[derive(Debug, Deserialize)]
+struct ProgressTemplateLine {
+    downloaded_bytes: Option<f64>,
+    total_bytes: Option<f64>,
+    total_bytes_estimate: Option<f64>,
+    speed: Option<f64>,
+    eta: Option<f64>,
+    fragment_index: Option<u64>,
+    fragment_count: Option<u64>,
+}
+
+// Helper function to check (once) whether the installed yt-dlp supports --progress-template.
+// Versions are dates like "2024.10.07", so they compare correctly as strings.
+fn supports_progress_template() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        get_yt_dlp_version()
+            .map(|version| version.as_str() >= PROGRESS_TEMPLATE_MIN_VERSION)
+            .unwrap_or(false)
+    })
+}
+
+// Helper function to parse a PROGRESS_TEMPLATE line. Progress comes from the bytes, using
+// the size estimate when the exact total isn't known, or else from the fragment count.
+// Example: [progress] {"status": "downloading", "downloaded_bytes": 5242880, "total_bytes": 104857600, ...}
+fn parse_progress_template(line: &str) -> Option<ProgressUpdate> {
+    let json = line.strip_prefix(PROGRESS_PREFIX)?.trim();
+    let fields: ProgressTemplateLine = serde_json::from_str(json).ok()?;
+    let downloaded_bytes = fields.downloaded_bytes.map(|b| b as u64);
+    let total_bytes = fields.total_bytes.or(fields.total_bytes_estimate).map(|b| b as u64);
+    
+    let progress = match (downloaded_bytes, total_bytes, fields.fragment_index, fields.fragment_count) {
+        (Some(downloaded), Some(total), _, _) if total > 0 => (downloaded as f64 / total as f64 * 100.0).min(100.0),
+        (_, _, Some(index), Some(count)) if count > 0 => (index as f64 / count as f64 * 100.0).min(100.0),
+        _ => return None,
+    };
+    
+    Some(ProgressUpdate {
+        progress,
+        speed: fields.speed.map(format_speed).unwrap_or_default(),
+        eta: fields.eta.map(|e| format_eta(e as u64)).unwrap_or_default(),
+        speed_bps: fields.speed,
+        downloaded_bytes,
+        total_bytes,
+        fragment_index: fields.fragment_index,
+        fragment_count: fields.fragment_count,
+    })
+}
+
+// Helper function to format a byte count the way yt-dlp does, e.g. "5.00MiB"
+fn format_byte_size(bytes: f64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2}{}", value, units[unit])
+}
+
+// Helper function to format bytes per second the way yt-dlp does, e.g. "5.00MiB/s"
+fn format_speed(bps: f64) -> String {
+    format!("{}/s", format_byte_size(bps))
+}
+
+// Helper function to format seconds the way yt-dlp does, e.g. "01:05" or "1:02:03"
+fn format_eta(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+// Helper function to convert a yt-dlp speed such as "512.00KiB/s" into bytes per second
+fn parse_speed_bps(speed: &str) -> Option<f64> {
+    let speed = speed.strip_suffix("/s")?;
+    let split = speed.find(|c: char| c.is_ascii_alphabetic())?;
+    let value: f64 = speed[..split].parse().ok()?;
+    let multiplier = match &speed[split..] {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * multiplier)
+}
+
+// Helper function to validate -S criteria such as "+size,res:1080,vcodec:av1"
+fn validate_format_sort(criteria: &str) -> Result<(), String> {
+    for criterion in criteria.split(',') {
+        let criterion = criterion.trim();
+        if criterion.is_empty() {
+            return Err("Format sort criteria can't be empty".to_string());
+        }
+        
+        // Each criterion is an optional "+" (reverse), a field, and an optional ":value" or "~value"
+        let field = criterion
+            .trim_start_matches('+')
+            .split([':', '~'])
+            .next()
+            .unwrap_or("");
+        if !FORMAT_SORT_FIELDS.contains(&field) {
+            return Err(format!("Unknown format sort field: {}", field));
         }
     }
     
-    // Try to find using 'which' command
-    if let Ok(output) = Command::new("which").arg("ffmpeg").output() {
-        if output.status.success() {
-            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !path.is_empty() {
-                return Some(path);
-            }
-        }
+    Ok(())
+}
+
+// Helper function to check whether a URL points at a playlist or channel rather than a single video
+fn is_playlist_url(url: &str) -> bool {
+    url.contains("list=")
+        || url.contains("/playlist")
+        || url.contains("/@")
+        || url.contains("/channel/")
+        || url.contains("/c/")
+        || url.contains("/user/")
+}
+
+// Helper function to parse a per-item error that yt-dlp reports and skips with --ignore-errors
+// Examples:
+//   ERROR: [youtube] abc123def45: Private video. Sign in if you've been granted access to this video
+//   ERROR: [youtube] abc123def45: Video unavailable. This video has been removed by the uploader
+fn parse_playlist_item_error(line: &str) -> Option<(String, PlaylistSkipReason, String)> {
+    let error_regex = regex::Regex::new(r"^ERROR: \[[^\]]+\] ([\w-]+): (.+)$").ok()?;
+    let caps = error_regex.captures(line.trim())?;
+    let video_id = caps.get(1)?.as_str().to_string();
+    let message = caps.get(2)?.as_str().to_string();
+    
+    let deleted_patterns = [
+        "video unavailable",
+        "has been removed",
+        "no longer available",
+        "account associated with this video has been terminated",
+        "deleted video",
+    ];
+    let message_lower = message.to_lowercase();
+    let reason = if message_lower.contains("private video") {
+        PlaylistSkipReason::Private
+    } else if deleted_patterns.iter().any(|pattern| message_lower.contains(pattern)) {
+        PlaylistSkipReason::Deleted
+    } else {
+        PlaylistSkipReason::Failed
+    };
+    
+    Some((video_id, reason, message))
+}
+
+// What kind of content a URL points at
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum UrlKind {
+    Video,
+    Short,
+    Playlist,
+    Channel,
+    Live,
+    Unknown,
+}
+
+// Helper function to classify a URL from its structure alone.
+// Returns None for single-video URLs, which may turn out to be live streams.
+fn classify_url_structure(url: &str) -> Option<UrlKind> {
+    let youtube_regex = regex::Regex::new(r"^(https?://)?((www|m|music)\.)?(youtube\.com|youtu\.be)/").ok()?;
+    if !youtube_regex.is_match(url) {
+        return None;
+    }
+    
+    let channel_regex = regex::Regex::new(r"youtube\.com/(@[^/?#]+|channel/[^/?#]+|c/[^/?#]+|user/[^/?#]+)(/[^?#]*)?").ok()?;
+    if url.contains("/shorts/") {
+        Some(UrlKind::Short)
+    } else if url.contains("/live/") {
+        Some(UrlKind::Live)
+    } else if url.contains("list=") {
+        Some(UrlKind::Playlist)
+    } else if let Some(caps) = channel_regex.captures(url) {
+        // e.g. youtube.com/@channel/live redirects to the channel's current stream
+        let tab = caps.get(2).map(|m| m.as_str().trim_matches('/')).unwrap_or("");
+        Some(if tab == "live" { UrlKind::Live } else { UrlKind::Channel })
+    } else {
+        None
+    }
+}
+
+// Channel tabs that get_channel_info can list
+const CHANNEL_TABS: [&str; 3] = ["videos", "shorts", "streams"];
+
+// One video in a channel tab
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelEntry {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub duration: Option<f64>,
+    pub view_count: Option<u64>,
+    pub thumbnail: Option<String>,
+}
+
+// A page of a channel tab, newest first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelInfo {
+    pub channel_id: Option<String>,
+    pub name: String,
+    pub tab: String,
+    // 1-based page number
+    pub page: u32,
+    pub entries: Vec<ChannelEntry>,
+    pub has_more: bool,
+}
+
+// Helper function to get a channel's base URL (e.g. https://www.youtube.com/@name) from
+// any of its pages
+fn channel_base_url(url: &str) -> Option<String> {
+    let channel_regex = regex::Regex::new(r"youtube\.com/(@[^/?#]+|channel/[^/?#]+|c/[^/?#]+|user/[^/?#]+)").ok()?;
+    let caps = channel_regex.captures(url)?;
+    Some(format!("https://www.youtube.com/{}", caps.get(1)?.as_str()))
+}
+
+// Helper function to read the entries of a --flat-playlist JSON dump
+fn parse_flat_entries(json: &serde_json::Value) -> Vec<ChannelEntry> {
+    json["entries"].as_array()
+        .map(|entries| entries.iter().filter_map(|entry| {
+            let id = entry["id"].as_str()?.to_string();
+            let url = entry["url"].as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+            Some(ChannelEntry {
+                title: entry["title"].as_str().unwrap_or(&id).to_string(),
+                duration: entry["duration"].as_f64(),
+                view_count: entry["view_count"].as_u64(),
+                thumbnail: entry["thumbnails"].as_array()
+                    .and_then(|thumbnails| thumbnails.last())
+                    .and_then(|t| t["url"].as_str())
+                    .map(str::to_string),
+                id,
+                url,
+            })
+        }).collect())
+        .unwrap_or_default()
+}
+
+// List one page of a channel's videos, shorts or streams tab (default "videos")
+#[tauri::command]
+pub async fn get_channel_info(
+    url: String,
+    tab: Option<String>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<ChannelInfo, String> {
+    let base_url = channel_base_url(&url).ok_or(format!("Not a channel URL: {}", url))?;
+    let tab = tab.unwrap_or_else(|| "videos".to_string());
+    if !CHANNEL_TABS.contains(&tab.as_str()) {
+        return Err(format!("Unknown channel tab: {}", tab));
+    }
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(30).clamp(1, 200);
+    
+    // Ask for one extra entry to tell whether there is another page
+    let start = (page - 1) * page_size + 1;
+    let end = start + page_size;
+    let tab_url = format!("{}/{}", base_url, tab);
+    let output = run_yt_dlp_info_async(&[
+        "--flat-playlist",
+        "--dump-single-json",
+        "--no-warnings",
+        "--playlist-start", &start.to_string(),
+        "--playlist-end", &end.to_string(),
+        &tab_url
+    ], &read_settings().auth_method).await?;
+    
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to list channel: {}", stderr.trim()));
     }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse channel info: {}", e))?;
     
-    None
+    let mut entries = parse_flat_entries(&json);
+    let has_more = entries.len() > page_size as usize;
+    entries.truncate(page_size as usize);
+    
+    Ok(ChannelInfo {
+        channel_id: json["channel_id"].as_str().map(str::to_string),
+        name: json["channel"].as_str()
+            .or(json["uploader"].as_str())
+            .or(json["title"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        tab,
+        page,
+        entries,
+        has_more,
+    })
 }
 
-// Get video info using yt-dlp
+// Queue downloads of the selected channel entries, each with the given options and its
+// own URL. Returns the queued IDs in the same order.
 #[tauri::command]
-pub async fn get_video_info(url: String) -> Result<VideoInfo, String> {
-    let yt_dlp_info = find_yt_dlp_with_resources()?;
-    let cookies_path = get_cookies_path()?;
+pub async fn download_channel_entries(
+    urls: Vec<String>,
+    options: DownloadOptions,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    for url in &urls {
+        validate_url(url.clone())?;
+    }
+    
+    let mut ids = Vec::new();
+    for url in urls {
+        let entry_options = DownloadOptions {
+            url,
+            ..options.clone()
+        };
+        ids.push(enqueue_download(entry_options, app.clone()).await?);
+    }
+    Ok(ids)
+}
 
-    let mut cmd = Command::new(&yt_dlp_info.path);
-    configure_command_env(&mut cmd, &yt_dlp_info);
+// Outcome of one link from import_urls_from_file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedUrl {
+    // 1-based line in the imported file
+    pub line: usize,
+    pub url: String,
+    pub title: Option<String>,
+    pub duration: Option<f64>,
+    // Queue ID if the link was enqueued
+    pub download_id: Option<String>,
+    pub error: Option<String>,
+}
+
+// Helper function to pull the links out of a plain text or CSV URL list. Each line
+// contributes its first field that is an http(s) link or a YouTube URL without the
+// scheme (e.g. "youtu.be/dQw4w9WgXcQ"); blank lines, # comments and
+// rows without a link (e.g. a CSV header) are skipped, as are repeated links.
+fn parse_url_list(content: &str) -> Vec<(usize, String)> {
+    let mut seen = HashSet::new();
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#'))
+        .filter_map(|(index, line)| {
+            let url = line.split([',', ';', '\t'])
+                .map(|field| field.trim().trim_matches(['"', '\'']).trim())
+                .find(|field| {
+                    field.starts_with("http://") || field.starts_with("https://") || validate_url(field.to_string()).is_ok()
+                })?;
+            Some((index + 1, url.to_string()))
+        })
+        .filter(|(_, url)| seen.insert(url.clone()))
+        .collect()
+}
+
+// Import a plain text or CSV file of links and queue every valid one with the default
+// download options. Metadata is fetched in parallel batches of max_concurrent_info_fetches
+// so dead links are reported instead of queued. Results follow the order of the file.
+#[tauri::command]
+pub async fn import_urls_from_file(path: String, app: AppHandle) -> Result<Vec<ImportedUrl>, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read URL list: {}", e))?;
+    let links = parse_url_list(&content);
+    if links.is_empty() {
+        return Err("No links found in the file".to_string());
+    }
     
-    let output = cmd
-        .args([
-            "--cookies", &cookies_path,
-            "--dump-json", 
-            "--no-playlist",
-            &url
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    // Only start as many fetches as may run at once, rather than parking a blocking
+    // thread per link on the info fetch limiter
+    let batch_size = read_settings().max_concurrent_info_fetches.max(1);
+    let mut results = Vec::with_capacity(links.len());
+    for batch in links.chunks(batch_size) {
+        let tasks: Vec<_> = batch.iter().cloned().map(|(line, url)| {
+            tauri::async_runtime::spawn_blocking(move || fetch_imported_url(line, url))
+        }).collect();
+        
+        for task in tasks {
+            let mut imported = task.await.map_err(|e| format!("Failed to import URL: {}", e))?;
+            if imported.error.is_none() {
+                let options = DownloadOptions {
+                    url: imported.url.clone(),
+                    ..Default::default()
+                };
+                match enqueue_download(options, app.clone()).await {
+                    Ok(id) => imported.download_id = Some(id),
+                    Err(e) => imported.error = Some(e),
+                }
+            }
+            results.push(imported);
+        }
+    }
+    Ok(results)
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp error: {}", stderr));
+// Helper function to check one link from an imported URL list, looking up its title
+// and duration
+fn fetch_imported_url(line: usize, url: String) -> ImportedUrl {
+    let mut imported = ImportedUrl {
+        line,
+        url: url.clone(),
+        title: None,
+        duration: None,
+        download_id: None,
+        error: None,
+    };
+    if let Err(e) = validate_url(url.clone()) {
+        imported.error = Some(e);
+        return imported;
     }
+    
+    // Flat extraction keeps playlist links to a single request
+    let output = run_yt_dlp_info(
+        &["--flat-playlist", "--dump-single-json", "--no-warnings", &url],
+        &read_settings().auth_method,
+    );
+    match output {
+        Ok(output) if output.status.success() => {
+            let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+            imported.title = json["title"].as_str().map(|title| title.to_string());
+            imported.duration = json["duration"].as_f64();
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().rfind(|line| line.starts_with("ERROR")).unwrap_or(stderr.trim());
+            imported.error = Some(format!("yt-dlp error: {}", message.trim()));
+        }
+        Err(e) => imported.error = Some(e),
+    }
+    imported
+}
 
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+// Newest entries fetched per subscription check: the first of a channel's uploads, or
+// the last of a playlist, since playlists usually grow at the end
+const SUBSCRIPTION_CHECK_ENTRIES: u32 = 30;
 
-    Ok(VideoInfo {
-        id: json["id"].as_str().unwrap_or("").to_string(),
-        title: json["title"].as_str().unwrap_or("").to_string(),
-        description: json["description"].as_str().unwrap_or("").to_string(),
-        duration: json["duration"].as_u64().unwrap_or(0),
-        uploader: json["uploader"].as_str().unwrap_or("").to_string(),
-        thumbnail: json["thumbnail"].as_str().unwrap_or("").to_string(),
-    })
+// Video IDs remembered per subscription; enough to cover several checks' worth of entries
+const SUBSCRIPTION_SEEN_LIMIT: usize = 500;
+
+// A channel or playlist watched for new uploads, saved to subscriptions.json
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    pub name: String,
+    // Queue new uploads for download, limited to titles containing title_filter if set
+    pub auto_download: bool,
+    pub title_filter: Option<String>,
+    // Unix timestamp (seconds) of the last check
+    pub last_checked: u64,
+    // IDs of videos already seen, newest first
+    seen_ids: Vec<String>,
 }
 
-// Combined response for video info, formats, and subtitles
+// New uploads found by a subscription check, sent as the new-videos event
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CombinedVideoInfo {
-    pub info: VideoInfo,
-    pub formats: Vec<VideoFormat>,
-    pub subtitles: Vec<Subtitle>,
+pub struct NewVideos {
+    pub subscription_id: String,
+    pub name: String,
+    pub videos: Vec<ChannelEntry>,
+    // IDs of the downloads queued for them when auto_download is on
+    pub queued: Vec<String>,
 }
 
-// Get video info, formats, and subtitles in a single yt-dlp call (faster)
-#[tauri::command]
-pub async fn get_video_info_combined(url: String) -> Result<CombinedVideoInfo, String> {
-    let yt_dlp_info = find_yt_dlp_with_resources()?;
-    let cookies_path = get_cookies_path()?;
+// Held while subscriptions.json is read and written back
+static SUBSCRIPTIONS_LOCK: Mutex<()> = Mutex::new(());
 
-    let mut cmd = Command::new(&yt_dlp_info.path);
-    configure_command_env(&mut cmd, &yt_dlp_info);
+// Helper function to get the path of the subscriptions file
+fn get_subscriptions_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("subscriptions.json");
     
-    let output = cmd
-        .args([
-            "--cookies", &cookies_path,
-            "--dump-json", 
-            "--no-playlist",
-            &url
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    
+    Ok(path)
+}
 
+// Helper function to read the subscriptions (empty if missing or invalid)
+fn read_subscriptions() -> Vec<Subscription> {
+    get_subscriptions_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Helper function to write the subscriptions back to disk
+fn write_subscriptions(subscriptions: &[Subscription]) -> Result<(), String> {
+    let path = get_subscriptions_path()?;
+    let json = serde_json::to_string_pretty(subscriptions)
+        .map_err(|e| format!("Failed to serialize subscriptions: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write subscriptions: {}", e))
+}
+
+// Helper function to fetch the newest entries of a channel's videos tab or a playlist,
+// along with its name
+fn fetch_subscription_entries(url: &str) -> Result<(String, Vec<ChannelEntry>), String> {
+    let (list_url, range_arg, range) = match channel_base_url(url) {
+        Some(base) => (format!("{}/videos", base), "--playlist-end", SUBSCRIPTION_CHECK_ENTRIES.to_string()),
+        None if matches!(classify_url_structure(url), Some(UrlKind::Playlist)) => {
+            (url.to_string(), "--playlist-items", format!("-{}:", SUBSCRIPTION_CHECK_ENTRIES))
+        }
+        None => (url.to_string(), "--playlist-end", SUBSCRIPTION_CHECK_ENTRIES.to_string()),
+    };
+    let output = run_yt_dlp_info(&[
+        "--flat-playlist",
+        "--dump-single-json",
+        "--no-warnings",
+        range_arg, &range,
+        &list_url
+    ], &read_settings().auth_method)?;
+    
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp error: {}", stderr));
+        return Err(format!("Failed to check subscription: {}", stderr.trim()));
     }
-
     let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| format!("Failed to parse subscription: {}", e))?;
+    
+    let name = json["channel"].as_str()
+        .or(json["title"].as_str())
+        .unwrap_or(url)
+        .to_string();
+    Ok((name, parse_flat_entries(&json)))
+}
 
-    // Extract video info
-    let info = VideoInfo {
-        id: json["id"].as_str().unwrap_or("").to_string(),
-        title: json["title"].as_str().unwrap_or("").to_string(),
-        description: json["description"].as_str().unwrap_or("").to_string(),
-        duration: json["duration"].as_u64().unwrap_or(0),
-        uploader: json["uploader"].as_str().unwrap_or("").to_string(),
-        thumbnail: json["thumbnail"].as_str().unwrap_or("").to_string(),
+// Helper function to identify the channel or playlist a subscription URL points at, so
+// different links to the same one (e.g. ".../@name" and ".../@name/videos") match
+fn subscription_key(url: &str) -> String {
+    if let Some(base) = channel_base_url(url) {
+        return base;
+    }
+    url.split(['?', '&'])
+        .find_map(|param| param.strip_prefix("list="))
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| url.trim_end_matches('/').to_string())
+}
+
+// Subscribe to a channel or playlist. Its current videos count as seen, so only later
+// uploads are reported.
+#[tauri::command]
+pub async fn add_subscription(
+    url: String,
+    auto_download: bool,
+    title_filter: Option<String>,
+) -> Result<Subscription, String> {
+    if !matches!(classify_url_structure(&url), Some(UrlKind::Channel | UrlKind::Playlist)) {
+        return Err(format!("Not a channel or playlist URL: {}", url));
+    }
+    let key = subscription_key(&url);
+    if read_subscriptions().iter().any(|s| subscription_key(&s.url) == key) {
+        return Err(format!("Already subscribed: {}", url));
+    }
+    
+    let list_url = url.clone();
+    let (name, entries) = tauri::async_runtime::spawn_blocking(move || fetch_subscription_entries(&list_url))
+        .await
+        .map_err(|e| format!("Failed to check subscription: {}", e))??;
+    let subscription = Subscription {
+        id: uuid::Uuid::new_v4().to_string(),
+        url,
+        name,
+        auto_download,
+        title_filter: title_filter.filter(|filter| !filter.trim().is_empty()),
+        last_checked: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        seen_ids: entries.into_iter().map(|entry| entry.id).collect(),
     };
+    
+    let _guard = SUBSCRIPTIONS_LOCK.lock().unwrap();
+    let mut subscriptions = read_subscriptions();
+    // Another add for the same channel may have finished while this one was fetching
+    if subscriptions.iter().any(|s| subscription_key(&s.url) == key) {
+        return Err(format!("Already subscribed: {}", subscription.url));
+    }
+    subscriptions.push(subscription.clone());
+    write_subscriptions(&subscriptions)?;
+    Ok(subscription)
+}
 
-    // Extract formats
-    let mut formats = Vec::new();
-    if let Some(format_array) = json["formats"].as_array() {
-        for format in format_array {
-            if let Some(ext) = format["ext"].as_str() {
-                if ext == "mp4" || ext == "webm" || ext == "mkv" {
-                    formats.push(VideoFormat {
-                        id: format["format_id"].as_str().unwrap_or("").to_string(),
-                        ext: ext.to_string(),
-                        resolution: format["resolution"].as_str().unwrap_or("").to_string(),
-                        fps: format["fps"].as_u64().unwrap_or(0) as u32,
-                        filesize: format["filesize"].as_u64(),
-                        vcodec: format["vcodec"].as_str().unwrap_or("").to_string(),
-                        acodec: format["acodec"].as_str().unwrap_or("").to_string(),
-                    });
+// Get all subscriptions
+#[tauri::command]
+pub async fn get_subscriptions() -> Result<Vec<Subscription>, String> {
+    Ok(read_subscriptions())
+}
+
+// Unsubscribe
+#[tauri::command]
+pub async fn remove_subscription(id: String) -> Result<(), String> {
+    let _guard = SUBSCRIPTIONS_LOCK.lock().unwrap();
+    let mut subscriptions = read_subscriptions();
+    let count = subscriptions.len();
+    subscriptions.retain(|s| s.id != id);
+    if subscriptions.len() == count {
+        return Err(format!("Subscription not found: {}", id));
+    }
+    write_subscriptions(&subscriptions)
+}
+
+// Helper function to check subscriptions for new uploads, emitting new-videos for each one
+// that has some and queueing downloads for auto_download ones. With `only_due`, only
+// subscriptions whose check interval has passed are checked.
+fn check_subscriptions(app: &AppHandle, only_due: bool) -> Vec<NewVideos> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let interval = read_settings().subscription_check_interval_mins * 60;
+    let due: Vec<Subscription> = read_subscriptions()
+        .into_iter()
+        .filter(|s| !only_due || now.saturating_sub(s.last_checked) >= interval)
+        .collect();
+    
+    let mut results = Vec::new();
+    for subscription in due {
+        let entries = match fetch_subscription_entries(&subscription.url) {
+            Ok((_, entries)) => entries,
+            Err(e) => {
+                eprintln!("Failed to check subscription {}: {}", subscription.url, e);
+                continue;
+            }
+        };
+        let videos: Vec<ChannelEntry> = entries.iter()
+            .filter(|entry| !subscription.seen_ids.contains(&entry.id))
+            .cloned()
+            .collect();
+        
+        let mut queued = Vec::new();
+        if subscription.auto_download {
+            let filter = subscription.title_filter.as_ref().map(|f| f.to_lowercase());
+            for video in &videos {
+                if filter.as_ref().is_some_and(|f| !video.title.to_lowercase().contains(f)) {
+                    continue;
+                }
+                let options = DownloadOptions {
+                    url: video.url.clone(),
+                    title: Some(video.title.clone()),
+                    ..Default::default()
+                };
+                match tauri::async_runtime::block_on(enqueue_download(options, app.clone())) {
+                    Ok(id) => queued.push(id),
+                    Err(e) => eprintln!("Failed to queue {}: {}", video.url, e),
                 }
             }
         }
+        
+        // Record the check; re-read so changes made meanwhile aren't lost
+        {
+            let _guard = SUBSCRIPTIONS_LOCK.lock().unwrap();
+            let mut subscriptions = read_subscriptions();
+            if let Some(stored) = subscriptions.iter_mut().find(|s| s.id == subscription.id) {
+                stored.last_checked = now;
+                let mut seen: Vec<String> = videos.iter().map(|v| v.id.clone()).collect();
+                seen.append(&mut stored.seen_ids);
+                seen.truncate(SUBSCRIPTION_SEEN_LIMIT);
+                stored.seen_ids = seen;
+            }
+            if let Err(e) = write_subscriptions(&subscriptions) {
+                eprintln!("Failed to update subscriptions: {}", e);
+            }
+        }
+        
+        if videos.is_empty() {
+            continue;
+        }
+        let new_videos = NewVideos {
+            subscription_id: subscription.id,
+            name: subscription.name,
+            videos,
+            queued,
+        };
+        if let Err(e) = app.emit("new-videos", &new_videos) {
+            eprintln!("Failed to emit new-videos: {}", e);
+        }
+        results.push(new_videos);
     }
+    results
+}
 
-    // Extract subtitles
-    let mut subtitles = Vec::new();
-    if let Some(subs) = json["subtitles"].as_object() {
-        for (lang, data) in subs {
-            if let Some(sub_array) = data.as_array() {
-                if let Some(first_sub) = sub_array.first() {
-                    subtitles.push(Subtitle {
-                        lang: lang.clone(),
-                        name: first_sub["name"].as_str().unwrap_or(lang).to_string(),
-                        format: first_sub["ext"].as_str().unwrap_or("srt").to_string(),
-                    });
+// Check every subscription for new uploads now
+#[tauri::command]
+pub async fn check_subscriptions_now(app: AppHandle) -> Result<Vec<NewVideos>, String> {
+    tauri::async_runtime::spawn_blocking(move || check_subscriptions(&app, false))
+        .await
+        .map_err(|e| format!("Failed to check subscriptions: {}", e))
+}
+
+// Start the background task that checks subscriptions once their interval has passed.
+// Called from setup in main.rs.
+pub fn start_subscription_checker(app: AppHandle) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(60));
+            if *app.state::<DownloadManager>().shutting_down.lock().unwrap() {
+                break;
+            }
+            check_subscriptions(&app, true);
+        }
+    });
+}
+
+// How often the clipboard is read while clipboard_monitoring is on
+const CLIPBOARD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1000);
+
+// Start the background task that watches the clipboard for video links while the
+// clipboard_monitoring setting is on. Called from setup in main.rs.
+pub fn start_clipboard_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut clipboard: Option<arboard::Clipboard> = None;
+        // Text already on the clipboard when monitoring turns on isn't treated as new
+        let mut last_text: Option<String> = None;
+        loop {
+            std::thread::sleep(CLIPBOARD_POLL_INTERVAL);
+            if *app.state::<DownloadManager>().shutting_down.lock().unwrap() {
+                break;
+            }
+            
+            let settings = read_settings();
+            if !settings.clipboard_monitoring {
+                clipboard = None;
+                last_text = None;
+                continue;
+            }
+            if clipboard.is_none() {
+                match arboard::Clipboard::new() {
+                    Ok(opened) => clipboard = Some(opened),
+                    Err(e) => {
+                        eprintln!("Failed to open clipboard: {}", e);
+                        continue;
+                    }
+                }
+            }
+            
+            // Non-text contents (images, files) read as an error and are ignored
+            let Some(text) = clipboard.as_mut().and_then(|c| c.get_text().ok()) else {
+                continue;
+            };
+            let previous = last_text.replace(text.clone());
+            if previous.is_none_or(|previous| previous == text) {
+                continue;
+            }
+            
+            let url = text.trim().to_string();
+            if url.contains(char::is_whitespace) || validate_url(url.clone()).is_err() {
+                continue;
+            }
+            let kind = classify_url_structure(&url);
+            
+            if !settings.clipboard_auto_download {
+                let emit_result = app.emit("url-detected", serde_json::json!({
+                    "url": url,
+                    "kind": kind
+                }));
+                if let Err(e) = emit_result {
+                    eprintln!("Failed to emit url-detected: {}", e);
                 }
+                continue;
+            }
+            
+            let result = tauri::async_runtime::block_on(start_default_download(url.clone(), app.clone()));
+            let payload = match result {
+                Ok(id) => serde_json::json!({ "url": url, "kind": kind, "id": id }),
+                Err(e) => serde_json::json!({ "url": url, "kind": kind, "error": e }),
+            };
+            if let Err(e) = app.emit("url-detected", payload) {
+                eprintln!("Failed to emit url-detected: {}", e);
             }
         }
+    });
+}
+
+// Classify a URL as a video, short, playlist, channel, or live stream. Clear-cut YouTube
+// URLs are classified from their structure; anything else is probed with yt-dlp.
+#[tauri::command]
+pub async fn classify_url(url: String) -> Result<UrlKind, String> {
+    if let Some(kind) = classify_url_structure(&url) {
+        return Ok(kind);
+    }
+    
+    let output = run_yt_dlp_info_async(
+        &["--flat-playlist", "--dump-single-json", "--no-warnings", &url],
+        &read_settings().auth_method,
+    ).await?;
+    if !output.status.success() {
+        return Ok(UrlKind::Unknown);
     }
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return Ok(UrlKind::Unknown);
+    };
+    
+    let kind = if json["_type"].as_str() == Some("playlist") {
+        UrlKind::Playlist
+    } else if matches!(json["live_status"].as_str(), Some("is_live" | "is_upcoming")) {
+        UrlKind::Live
+    } else {
+        UrlKind::Video
+    };
+    Ok(kind)
+}
 
-    Ok(CombinedVideoInfo { info, formats, subtitles })
+// Helper function to get the download archive file for a playlist or channel URL.
+// Playlists are keyed by their list ID; other URLs by a hash of the URL.
+fn get_playlist_archive_path(url: &str) -> Result<PathBuf, String> {
+    let list_id = url.split(['?', '&'])
+        .find_map(|param| param.strip_prefix("list="))
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    let key = match list_id {
+        Some(id) => id.to_string(),
+        None => blake3::hash(url.trim_end_matches('/').as_bytes()).to_hex()[..16].to_string(),
+    };
+    
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("archives");
+    
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    
+    path.push(format!("{}.txt", key));
+    Ok(path)
 }
 
-// Get available formats
+// Forget which items of a playlist were downloaded so the next run fetches everything again
 #[tauri::command]
-pub async fn get_available_formats(url: String) -> Result<Vec<VideoFormat>, String> {
-    let yt_dlp_info = find_yt_dlp_with_resources()?;
-    let cookies_path = get_cookies_path()?;
+pub async fn reset_playlist_archive(url: String) -> Result<(), String> {
+    let path = get_playlist_archive_path(&url)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to reset playlist archive: {}", e))?;
+    }
+    Ok(())
+}
 
-    // Use --dump-json to get JSON output (formats are included in the video info)
-    let mut cmd = Command::new(&yt_dlp_info.path);
-    configure_command_env(&mut cmd, &yt_dlp_info);
+// Helper function to combine match filter expressions into a single --match-filter value.
+// yt-dlp ORs repeated --match-filter flags, so the expressions are joined with "&" instead.
+fn build_match_filter(filters: &[String]) -> Result<String, String> {
+    let mut expressions = Vec::new();
+    for filter in filters {
+        let filter = filter.trim();
+        if filter.is_empty() {
+            return Err("Match filter expressions can't be empty".to_string());
+        }
+        if filter.chars().any(|c| c.is_control()) {
+            return Err(format!("Invalid match filter: {}", filter));
+        }
+        expressions.push(filter);
+    }
     
-    let output = cmd
-        .args([
-            "--cookies", &cookies_path,
-            "--dump-json", 
-            "--no-playlist",
-            &url
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp error: {}", stderr));
+    if expressions.is_empty() {
+        return Err("No match filter expressions given".to_string());
     }
+    Ok(expressions.join(" & "))
+}
 
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+// Helper function to parse the playlist item counter from yt-dlp output
+// Example: [download] Downloading item 3 of 10 (older versions say "video" instead of "item")
+fn parse_playlist_item(line: &str) -> Option<(u32, u32)> {
+    let item_regex = regex::Regex::new(r"\[download\] Downloading (?:item|video) (\d+) of (\d+)").ok()?;
+    let caps = item_regex.captures(line)?;
+    Some((caps.get(1)?.as_str().parse().ok()?, caps.get(2)?.as_str().parse().ok()?))
+}
 
-    let formats = json["formats"].as_array()
-        .ok_or("No formats found")?;
+// Helper function to get the title of the file being downloaded from yt-dlp output
+// Example: [download] Destination: /Users/me/Downloads/Some Title.f137.mp4
+fn parse_destination_title(line: &str) -> Option<String> {
+    let path = line.strip_prefix("[download] Destination: ")?;
+    let stem = std::path::Path::new(path.trim()).file_stem()?.to_string_lossy().to_string();
+    
+    // Drop the format suffix yt-dlp adds to intermediate files (e.g. ".f137")
+    let format_suffix = regex::Regex::new(r"\.f\d+$").ok()?;
+    Some(format_suffix.replace(&stem, "").to_string())
+}
 
-    let mut video_formats = Vec::new();
-    for format in formats {
-        if let Some(ext) = format["ext"].as_str() {
-            if ext == "mp4" || ext == "webm" || ext == "mkv" {
-                video_formats.push(VideoFormat {
-                    id: format["format_id"].as_str().unwrap_or("").to_string(),
-                    ext: ext.to_string(),
-                    resolution: format["resolution"].as_str().unwrap_or("").to_string(),
-                    fps: format["fps"].as_u64().unwrap_or(0) as u32,
-                    filesize: format["filesize"].as_u64(),
-                    vcodec: format["vcodec"].as_str().unwrap_or("").to_string(),
-                    acodec: format["acodec"].as_str().unwrap_or("").to_string(),
-                });
-            }
-        }
+// Helper function to get the final output file path from yt-dlp output.
+// Intermediate per-format files (e.g. "Title.f137.mp4") are ignored.
+// Examples:
+//   [download] Destination: /Users/me/Downloads/Some Title.mp4
+//   [download] /Users/me/Downloads/Some Title.mp4 has already been downloaded
+//   [Merger] Merging formats into "/Users/me/Downloads/Some Title.mp4"
+fn parse_output_path(line: &str) -> Option<String> {
+    let path = if let Some(path) = line.strip_prefix("[download] Destination: ") {
+        path.trim()
+    } else if let Some(rest) = line.strip_prefix("[download] ") {
+        rest.strip_suffix(" has already been downloaded")?.trim()
+    } else {
+        line.strip_prefix("[Merger] Merging formats into ")?.trim().trim_matches('"')
+    };
+    
+    let intermediate = regex::Regex::new(r"\.f\d+\.\w+$").ok()?;
+    if path.is_empty() || intermediate.is_match(path) {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+// Helper function to estimate remaining playlist time from the average per-item time
+fn estimate_remaining_secs(elapsed: std::time::Duration, completed: u32, total: u32) -> Option<u64> {
+    if completed == 0 {
+        return None;
     }
+    let per_item = elapsed.as_secs_f64() / completed as f64;
+    Some((per_item * total.saturating_sub(completed) as f64).round() as u64)
+}
 
-    Ok(video_formats)
+// Helper function to update the stored playlist progress and emit it to the frontend
+fn update_playlist_progress(app: &AppHandle, id: &str, update: impl FnOnce(&mut PlaylistProgress)) {
+    let manager = app.state::<DownloadManager>();
+    let snapshot = {
+        let mut playlists = manager.playlist_progress.lock().unwrap();
+        let progress = playlists.entry(id.to_string()).or_insert_with(|| PlaylistProgress {
+            id: id.to_string(),
+            completed: 0,
+            total: 0,
+            current_title: String::new(),
+            estimated_remaining_secs: None,
+        });
+        update(progress);
+        progress.clone()
+    };
+    
+    if let Err(e) = app.emit("playlist-progress", &snapshot) {
+        eprintln!("Failed to emit playlist-progress: {}", e);
+    }
 }
 
-// Get available subtitles
+// Get playlist progress
 #[tauri::command]
-pub async fn get_available_subtitles(url: String) -> Result<Vec<Subtitle>, String> {
-    let yt_dlp_info = find_yt_dlp_with_resources()?;
-    let cookies_path = get_cookies_path()?;
+pub async fn get_playlist_progress(id: String, manager: State<'_, DownloadManager>) -> Result<PlaylistProgress, String> {
+    let playlists = manager.playlist_progress.lock().unwrap();
+    playlists
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| "No playlist progress for this download".to_string())
+}
 
-    // Use --dump-json to get JSON output (subtitles are included in the video info)
-    let mut cmd = Command::new(&yt_dlp_info.path);
-    configure_command_env(&mut cmd, &yt_dlp_info);
+// Helper function to get the path of the downloads stopped with their partial file kept
+fn get_stopped_downloads_path() -> Result<PathBuf, String> {
+    let mut path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?;
+    path.push(".youtube-downloader");
+    path.push("stopped.json");
     
-    let output = cmd
-        .args([
-            "--cookies", &cookies_path,
-            "--dump-json", 
-            "--no-playlist",
-            &url
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp error: {}", stderr));
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
+    
+    Ok(path)
+}
 
-    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-    let mut subtitle_list = Vec::new();
-    
-    // Check both "subtitles" and "automatic_captions" fields
-    if let Some(subtitles) = json["subtitles"].as_object() {
-        for (lang, data) in subtitles {
-            if let Some(sub_array) = data.as_array() {
-                if let Some(first_sub) = sub_array.first() {
-                    subtitle_list.push(Subtitle {
-                        lang: lang.clone(),
-                        name: first_sub["name"].as_str().unwrap_or(lang).to_string(),
-                        format: first_sub["ext"].as_str().unwrap_or("srt").to_string(),
-                    });
-                }
-            }
-        }
-    }
+// Helper function to read the stopped downloads (empty if missing or invalid)
+fn read_stopped_downloads() -> Vec<StoppedDownload> {
+    get_stopped_downloads_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
 
-    Ok(subtitle_list)
+// Helper function to write the stopped downloads back to disk
+fn write_stopped_downloads(stopped: &[StoppedDownload]) -> Result<(), String> {
+    let path = get_stopped_downloads_path()?;
+    let stopped: Vec<StoppedDownload> = stopped.iter()
+        .map(|download| StoppedDownload {
+            options: download.options.persistable(),
+            ..download.clone()
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&stopped)
+        .map_err(|e| format!("Failed to serialize stopped downloads: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write stopped downloads: {}", e))
 }
 
-// Start download
+// Cancel download. With keep_partial, the .part file is renamed to .partial and recorded
+// so resume_download can continue from it later; otherwise yt-dlp's temp files are deleted.
 #[tauri::command]
-pub async fn start_download(
-    options: DownloadOptions,
+pub async fn cancel_download(
+    id: String,
+    keep_partial: Option<bool>,
     app: AppHandle,
-    _manager: State<'_, DownloadManager>,
-) -> Result<String, String> {
-    let download_id = uuid::Uuid::new_v4().to_string();
-    let app_clone = app.clone();
-
-    // Build yt-dlp command
-    let yt_dlp_info = find_yt_dlp_with_resources()?;
-    let cookies_path = get_cookies_path()?;
-    let mut cmd = Command::new(&yt_dlp_info.path);
+    manager: State<'_, DownloadManager>,
+) -> Result<(), String> {
+    let persisted = read_persisted_queue().into_iter().find(|d| d.id == id);
+    update_persisted_queue(|queue| queue.retain(|d| d.id != id));
     
-    // Configure PATH and JS runtime to include bundled resources (bun)
-    configure_command_env(&mut cmd, &yt_dlp_info);
+    // A queue entry that hasn't started has no process to stop
+    let queue_state = {
+        let mut queue = manager.queue.lock().unwrap();
+        queue.iter_mut().find(|entry| entry.id == id).map(|entry| {
+            let previous = entry.state;
+            if matches!(previous, QueueState::Pending | QueueState::Active) {
+                entry.state = QueueState::Cancelled;
+            }
+            previous
+        })
+    };
+    if queue_state.is_some() {
+        emit_queue_updated(&app);
+    }
+    if queue_state == Some(QueueState::Pending) {
+        return Ok(());
+    }
     
-    // Use cookies file for authentication
-    cmd.arg("--cookies").arg(&cookies_path);
+    let active = manager.active.lock().unwrap()
+        .get(&id)
+        .map(|d| (d.pid, d.part_path.clone(), d.destinations.clone()));
+    let Some((pid, part_path, destinations)) = active else {
+        // A queue entry that is still starting stops itself once it sees it was cancelled
+        if queue_state == Some(QueueState::Active) {
+            return Ok(());
+        }
+        return Err("Download not found".to_string());
+    };
+    terminate_process(pid.ok_or("Download is not running")?)?;
     
-    // Set ffmpeg location if found (required for merging video+audio)
-    if let Some(ffmpeg_path) = find_ffmpeg() {
-        // Get the directory containing ffmpeg
-        if let Some(ffmpeg_dir) = std::path::Path::new(&ffmpeg_path).parent() {
-            cmd.arg("--ffmpeg-location").arg(ffmpeg_dir);
+    // Wait for yt-dlp and its children to exit so nothing writes to the files afterwards
+    for _ in 0..50 {
+        if !manager.active.lock().unwrap().contains_key(&id) {
+            break;
         }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
     }
     
-    // Use best video+audio format and let yt-dlp merge them properly
-    // This avoids the MPEG-TS container issues and ensures seekable video
-    cmd.arg("-f").arg("bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best");
-    cmd.arg("--merge-output-format").arg("mp4");
+    if !keep_partial.unwrap_or(false) {
+        remove_download_temp_files(&destinations);
+        return Ok(());
+    }
+    let (Some(part_path), Some(persisted)) = (part_path, persisted) else {
+        return Err("No partial file to keep for this download".to_string());
+    };
     
-    cmd.arg("-o").arg(&options.output);
-    cmd.arg("--newline");
-    cmd.arg("--progress");
+    let partial_path = format!("{}.partial", part_path.strip_suffix(".part").unwrap_or(&part_path));
+    fs::rename(&part_path, &partial_path)
+        .map_err(|e| format!("Failed to keep partial file: {}", e))?;
     
-    // Always download English subtitles automatically (manual subs only, no auto-generated)
-    cmd.arg("--write-subs");
-    cmd.arg("--sub-langs").arg("en");
-    cmd.arg("--sub-format").arg("srt/best");
-    cmd.arg("--convert-subs").arg("srt");
-
-    if let Some(cookies) = &options.cookies {
-        cmd.arg("--cookies").arg(cookies);
-    }
-
-    cmd.arg(&options.url);
-
-    // Redirect stderr to stdout so we can capture all output
-    let mut child = cmd
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::inherit()) // Let errors show in terminal
-        .spawn()
-        .map_err(|e| format!("Failed to start download: {}", e))?;
+    let mut stopped = read_stopped_downloads();
+    stopped.retain(|d| d.id != id);
+    stopped.push(StoppedDownload {
+        id,
+        options: persisted.options,
+        partial_path,
+    });
+    write_stopped_downloads(&stopped)
+}
 
-    let download_id_for_task = download_id.clone();
-    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-    
-    // Spawn a thread to monitor the download progress
-    std::thread::spawn(move || {
-        use std::io::{BufRead, BufReader};
+// Helper function to delete yt-dlp's temp files for the given destinations:
+// "<dest>.part", "<dest>.ytdl", fragments named "<dest>.part-Frag<n>", finished
+// per-format streams (e.g. "Title.f137.mp4") and the merger's "Title.temp.<ext>"
+fn remove_download_temp_files(destinations: &[String]) {
+    let Ok(format_suffix) = regex::Regex::new(r"\.f\d+$") else {
+        return;
+    };
+    for destination in destinations {
+        let _ = fs::remove_file(format!("{}.part", destination));
+        let _ = fs::remove_file(format!("{}.ytdl", destination));
         
-        let reader = BufReader::new(stdout);
-        for line in reader.lines().map_while(Result::ok) {
-            eprintln!("yt-dlp: {}", line); // Debug output to terminal
-            
-            // Parse progress from yt-dlp output
-            if line.contains("[download]") && line.contains("%") {
-                if let Some(progress) = parse_progress(&line) {
-                    eprintln!("Emitting progress: {}% speed={} eta={}", progress.0, progress.1, progress.2);
-                    let emit_result = app_clone.emit("download-progress", serde_json::json!({
-                        "id": download_id_for_task.clone(),
-                        "progress": progress.0,
-                        "speed": progress.1,
-                        "eta": progress.2
-                    }));
-                    if let Err(e) = emit_result {
-                        eprintln!("Failed to emit progress: {}", e);
-                    }
+        let path = std::path::Path::new(destination);
+        let (Some(parent), Some(file_name), Some(stem)) = (path.parent(), path.file_name(), path.file_stem()) else {
+            continue;
+        };
+        let stem = stem.to_string_lossy();
+        if format_suffix.is_match(&stem) {
+            let _ = fs::remove_file(path);
+        }
+        
+        let fragment_prefix = format!("{}.part-Frag", file_name.to_string_lossy());
+        let merge_prefix = format!("{}.temp.", format_suffix.replace(&stem, ""));
+        if let Ok(entries) = fs::read_dir(parent) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with(&fragment_prefix) || name.starts_with(&merge_prefix) {
+                    let _ = fs::remove_file(entry.path());
                 }
             }
         }
+    }
+}
+
+// Helper function to send a job-control signal (e.g. "STOP" or "CONT") to a download's
+// process group, i.e. yt-dlp and any ffmpeg it started
+fn send_signal(pid: u32, signal: &str) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        let status = Command::new("kill")
+            .arg(format!("-{}", signal))
+            .arg("--")
+            .arg(format!("-{}", pid))
+            .status()
+            .map_err(|e| format!("Failed to signal process: {}", e))?;
         
-        // Wait for the process to finish
-        let status = child.wait();
-        eprintln!("Download finished with status: {:?}", status);
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("Failed to send SIG{} to process {}", signal, pid))
+        }
+    }
+    
+    #[cfg(not(unix))]
+    {
+        let _ = (pid, signal);
+        Err("Pausing downloads is not supported on this platform".to_string())
+    }
+}
+
+// Helper function to terminate a yt-dlp process, waking it first if it was paused
+fn terminate_process(pid: u32) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        let _ = send_signal(pid, "CONT");
+        send_signal(pid, "TERM")
+    }
+    
+    #[cfg(windows)]
+    {
+        let status = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .status()
+            .map_err(|e| format!("Failed to terminate process: {}", e))?;
         
-        // Emit completion event
-        eprintln!("Emitting download-complete for: {}", download_id_for_task);
-        let emit_result = app_clone.emit("download-complete", download_id_for_task);
-        if let Err(e) = emit_result {
-            eprintln!("Failed to emit download-complete: {}", e);
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("Failed to terminate process {}", pid))
         }
-    });
+    }
+}
 
-    Ok(download_id)
+// Stop every running download before the app exits. Interrupted downloads stay in
+// queue.json (running ones restart with --continue via restore_queue) and the partial
+// files are kept. Called from the exit handler in main.rs.
+pub fn shutdown_downloads(manager: &DownloadManager) -> Vec<String> {
+    *manager.shutting_down.lock().unwrap() = true;
+    *manager.queue_paused.lock().unwrap() = true;
+    
+    let downloads: Vec<(String, Option<u32>)> = manager.active.lock().unwrap()
+        .values()
+        .map(|d| (d.id.clone(), d.pid))
+        .collect();
+    let mut stopped = Vec::new();
+    for (id, pid) in downloads {
+        let Some(pid) = pid else {
+            continue;
+        };
+        match terminate_process(pid) {
+            Ok(()) => stopped.push(id),
+            Err(e) => eprintln!("Failed to stop download {}: {}", id, e),
+        }
+    }
+    
+    // Dropping the watcher stops the watch list from starting new downloads
+    manager.watch_list.lock().unwrap().take();
+    stopped
 }
 
-// Helper function to parse progress from yt-dlp output
-fn parse_progress(line: &str) -> Option<(f64, String, String)> {
-    // Example: [download]  45.2% of 100.00MiB at 5.00MiB/s ETA 00:10
-    let progress_regex = regex::Regex::new(r"(\d+\.?\d*)%.*?at\s+(\S+).*?ETA\s+(\S+)").ok()?;
+// Stop all downloads ahead of closing the app; returns the IDs that were stopped
+#[tauri::command]
+pub async fn prepare_shutdown(manager: State<'_, DownloadManager>) -> Result<Vec<String>, String> {
+    Ok(shutdown_downloads(&manager))
+}
+
+// Helper function to suspend or continue a single download and emit its new state
+fn set_download_paused(app: &AppHandle, manager: &DownloadManager, id: &str, pause: bool) -> Result<(), String> {
+    let mut active = manager.active.lock().unwrap();
+    let download = active.get_mut(id).ok_or("Download not found")?;
+    let pid = download.pid.ok_or("Download is not running")?;
     
-    if let Some(caps) = progress_regex.captures(line) {
-        let progress: f64 = caps.get(1)?.as_str().parse().ok()?;
-        let speed = caps.get(2)?.as_str().to_string();
-        let eta = caps.get(3)?.as_str().to_string();
-        return Some((progress, speed, eta));
+    send_signal(pid, if pause { "STOP" } else { "CONT" })?;
+    
+    let event = if pause {
+        download.state = DownloadState::Paused;
+        "download-paused"
+    } else {
+        download.state = DownloadState::Running;
+        "download-resumed"
+    };
+    drop(active);
+    
+    if let Err(e) = app.emit(event, id) {
+        eprintln!("Failed to emit {}: {}", event, e);
     }
+    Ok(())
+}
+
+// Pause download
+#[tauri::command]
+pub async fn pause_download(id: String, app: AppHandle, manager: State<'_, DownloadManager>) -> Result<(), String> {
+    set_download_paused(&app, &manager, &id, true)
+}
+
+// Resume a paused download, or restart one stopped with its partial file kept.
+// Returns the download's ID, which is new when a stopped download is restarted.
+#[tauri::command]
+pub async fn resume_download(id: String, app: AppHandle, manager: State<'_, DownloadManager>) -> Result<String, String> {
+    let mut stopped = read_stopped_downloads();
+    let Some(index) = stopped.iter().position(|d| d.id == id) else {
+        set_download_paused(&app, &manager, &id, false)?;
+        return Ok(id);
+    };
     
-    // Simpler fallback: just get percentage
-    let simple_regex = regex::Regex::new(r"(\d+\.?\d*)%").ok()?;
-    if let Some(caps) = simple_regex.captures(line) {
-        let progress: f64 = caps.get(1)?.as_str().parse().ok()?;
-        return Some((progress, "".to_string(), "".to_string()));
+    // Put the .part file back so yt-dlp continues from it
+    let download = stopped.remove(index);
+    if let Some(base) = download.partial_path.strip_suffix(".partial") {
+        fs::rename(&download.partial_path, format!("{}.part", base))
+            .map_err(|e| format!("Failed to restore partial file: {}", e))?;
     }
+    write_stopped_downloads(&stopped)?;
     
-    None
+    start_download(download.options, app, manager).await
 }
 
-// Cancel download
+// Pause all running downloads and stop dispatching new ones
 #[tauri::command]
-pub async fn cancel_download(id: String, manager: State<'_, DownloadManager>) -> Result<(), String> {
-    let mut downloads = manager.downloads.lock().unwrap();
-    if let Some(mut child) = downloads.remove(&id) {
-        child.kill().map_err(|e| format!("Failed to kill process: {}", e))?;
-        Ok(())
-    } else {
-        Err("Download not found".to_string())
+pub async fn pause_all_downloads(app: AppHandle, manager: State<'_, DownloadManager>) -> Result<Vec<String>, String> {
+    *manager.queue_paused.lock().unwrap() = true;
+    
+    let ids: Vec<String> = manager.active.lock().unwrap()
+        .values()
+        .filter(|d| d.state == DownloadState::Running)
+        .map(|d| d.id.clone())
+        .collect();
+    let mut paused_ids = Vec::new();
+    for id in ids {
+        match set_download_paused(&app, &manager, &id, true) {
+            Ok(()) => paused_ids.push(id),
+            Err(e) => eprintln!("Failed to pause download {}: {}", id, e),
+        }
+    }
+    
+    if let Err(e) = app.emit("all-paused", &paused_ids) {
+        eprintln!("Failed to emit all-paused: {}", e);
+    }
+    Ok(paused_ids)
+}
+
+// Resume all paused downloads and allow new ones to be dispatched again
+#[tauri::command]
+pub async fn resume_all_downloads(app: AppHandle, manager: State<'_, DownloadManager>) -> Result<Vec<String>, String> {
+    *manager.queue_paused.lock().unwrap() = false;
+    dispatch_queue(&app);
+    
+    let ids: Vec<String> = manager.active.lock().unwrap()
+        .values()
+        .filter(|d| d.state == DownloadState::Paused)
+        .map(|d| d.id.clone())
+        .collect();
+    let mut resumed_ids = Vec::new();
+    for id in ids {
+        match set_download_paused(&app, &manager, &id, false) {
+            Ok(()) => resumed_ids.push(id),
+            Err(e) => eprintln!("Failed to resume download {}: {}", id, e),
+        }
+    }
+    
+    if let Err(e) = app.emit("all-resumed", &resumed_ids) {
+        eprintln!("Failed to emit all-resumed: {}", e);
     }
+    Ok(resumed_ids)
+}
+
+// List running, queued, and paused downloads
+#[tauri::command]
+pub async fn list_active_downloads(manager: State<'_, DownloadManager>) -> Result<Vec<ActiveDownload>, String> {
+    Ok(manager.active.lock().unwrap().values().cloned().collect())
 }
 
 // Get download progress
 #[tauri::command]
-pub async fn get_download_progress(id: String) -> Result<DownloadProgress, String> {
-    // For now, return a placeholder
-    // In a real implementation, we would track actual progress
-    Ok(DownloadProgress {
-        id,
+pub async fn get_download_progress(id: String, manager: State<'_, DownloadManager>) -> Result<DownloadProgress, String> {
+    manager.progress.lock().unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| "Download not found".to_string())
+}
+
+// Helper function to create the progress of a download that hasn't output anything yet
+fn new_download_progress(id: &str) -> DownloadProgress {
+    DownloadProgress {
+        id: id.to_string(),
         progress: 0.0,
-        speed: "0B/s".to_string(),
-        eta: "0:00".to_string(),
-        downloaded: "0B".to_string(),
-        total_size: "0B".to_string(),
-    })
+        speed: String::new(),
+        eta: String::new(),
+        downloaded: String::new(),
+        total_size: String::new(),
+        downloaded_bytes: None,
+        total_bytes: None,
+        speed_bps: None,
+        fragment_index: None,
+        fragment_count: None,
+        phase: DownloadPhase::Starting,
+    }
 }
 
-// Save credentials
-#[tauri::command]
-pub async fn save_credentials(credentials: Credentials) -> Result<(), String> {
+// Helper function to apply a change to a download's tracked progress; returns the new state
+fn update_download_progress(app: &AppHandle, id: &str, update: impl FnOnce(&mut DownloadProgress)) -> DownloadProgress {
+    let manager = app.state::<DownloadManager>();
+    let mut downloads = manager.progress.lock().unwrap();
+    let progress = downloads.entry(id.to_string()).or_insert_with(|| new_download_progress(id));
+    update(progress);
+    progress.clone()
+}
+
+// Helper function to tell what yt-dlp is doing from an output line. With a merged
+// selection like "137+140" (video first), the stream is told by the ".f<id>." in its file name.
+fn parse_download_phase(line: &str, requested_formats: &[String]) -> Option<DownloadPhase> {
+    const POST_PROCESSOR_PREFIXES: [&str; 10] = [
+        "[Fixup", "[Embed", "[Metadata]", "[ThumbnailsConvertor]", "[SubtitlesConvertor]",
+        "[VideoConvertor]", "[VideoRemuxer]", "[ModifyChapters]", "[SplitChapters]", "[MoveFiles]",
+    ];
+    
+    if let Some(path) = line.strip_prefix("[download] Destination: ") {
+        if requested_formats.len() < 2 {
+            return Some(DownloadPhase::Downloading);
+        }
+        let file_name = std::path::Path::new(path.trim()).file_name()?.to_string_lossy().to_string();
+        let phase = match requested_formats.iter().position(|id| file_name.contains(&format!(".f{}.", id))) {
+            Some(0) => DownloadPhase::DownloadingVideo,
+            Some(_) => DownloadPhase::DownloadingAudio,
+            None => DownloadPhase::Downloading,
+        };
+        Some(phase)
+    } else if line.starts_with("[Merger]") {
+        Some(DownloadPhase::Merging)
+    } else if line.starts_with("[ExtractAudio]") {
+        Some(DownloadPhase::ExtractingAudio)
+    } else if POST_PROCESSOR_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+        Some(DownloadPhase::PostProcessing)
+    } else {
+        None
+    }
+}
+
+// Helper function to get credentials storage path
+fn get_credentials_path() -> Result<PathBuf, String> {
     let mut path = dirs::home_dir()
         .ok_or("Failed to get home directory")?;
     path.push(".youtube-downloader");
     path.push("credentials.json");
+    Ok(path)
+}
+
+// Helper function to read stored credentials
+fn read_credentials() -> Result<Credentials, String> {
+    let path = get_credentials_path()?;
+    
+    let content = fs::read_to_string(&path)
+        .map_err(|_| "No credentials found".to_string())?;
+    
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse credentials: {}", e))
+}
+
+// Helper function to write credentials to disk
+fn write_credentials(credentials: &Credentials) -> Result<(), String> {
+    let path = get_credentials_path()?;
     
     // Create directory if it doesn't exist
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
     
-    let json = serde_json::to_string_pretty(&credentials)
+    let json = serde_json::to_string_pretty(credentials)
         .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
     
-    fs::write(&path, json).map_err(|e| format!("Failed to write credentials: {}", e))?;
-    
-    Ok(())
+    fs::write(&path, json).map_err(|e| format!("Failed to write credentials: {}", e))
+}
+
+// Save credentials
+#[tauri::command]
+pub async fn save_credentials(credentials: Credentials) -> Result<(), String> {
+    write_credentials(&credentials)
 }
 
 // Load credentials
 #[tauri::command]
 pub async fn load_credentials() -> Result<Credentials, String> {
-    let mut path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?;
-    path.push(".youtube-downloader");
-    path.push("credentials.json");
+    read_credentials()
+}
+
+// Rewrite credentials.json in the current schema, filling in defaults for missing fields.
+// Returns whether the file had to be migrated.
+#[tauri::command]
+pub async fn migrate_credentials() -> Result<bool, String> {
+    let path = get_credentials_path()?;
+    if !path.exists() {
+        return Ok(false);
+    }
     
     let content = fs::read_to_string(&path)
-        .map_err(|_| "No credentials found".to_string())?;
-    
-    let credentials: Credentials = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to read credentials: {}", e))?;
+    let original: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse credentials: {}", e))?;
+    let credentials: Credentials = serde_json::from_value(original.clone())
         .map_err(|e| format!("Failed to parse credentials: {}", e))?;
     
-    Ok(credentials)
+    // Anything missing, renamed, or obsolete shows up as a difference after a round trip
+    let current = serde_json::to_value(&credentials)
+        .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+    if current == original {
+        return Ok(false);
+    }
+    
+    write_credentials(&credentials)?;
+    Ok(true)
 }
 
 // Clear credentials
 #[tauri::command]
 pub async fn clear_credentials() -> Result<(), String> {
-    let mut path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?;
-    path.push(".youtube-downloader");
-    path.push("credentials.json");
+    let path = get_credentials_path()?;
+    
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove credentials: {}", e))?;
+    
+    // Also drop tokens cached for the yt-dlp OAuth2 plugin
+    if let Ok(token_path) = get_oauth_token_path() {
+        let _ = fs::remove_file(token_path);
+    }
+    
+    Ok(())
+}
+
+// Helper function to detect the OAuth2 device-code prompt in yt-dlp output
+// Example: [youtube+oauth2] To give yt-dlp access to your account, go to  https://www.google.com/device  and enter code  ABC-DEF-GHI
+fn parse_oauth_prompt(line: &str) -> Option<(String, String)> {
+    let prompt_regex = regex::Regex::new(r"go to\s+(\S+)\s+and enter code\s+(\S+)").ok()?;
+    let caps = prompt_regex.captures(line)?;
+    Some((caps.get(1)?.as_str().to_string(), caps.get(2)?.as_str().to_string()))
+}
+
+// How long login_oauth waits for the device code to be entered
+const OAUTH_LOGIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// Log in with OAuth2 using the device-code flow of the yt-dlp OAuth2 plugin
+#[tauri::command]
+pub async fn login_oauth(app: AppHandle) -> Result<Credentials, String> {
+    let yt_dlp_info = find_yt_dlp_with_resources()?;
+    let cache_dir = get_oauth_cache_dir()?;
+    
+    // Remove any cached tokens so the plugin starts a fresh device-code flow
+    if let Ok(token_path) = get_oauth_token_path() {
+        let _ = fs::remove_file(token_path);
+    }
+    
+    let mut cmd = Command::new(&yt_dlp_info.path);
+    configure_command_env(&mut cmd, &yt_dlp_info);
+    apply_extractor_args(&mut cmd, &read_settings().extractor_args);
+    cmd.arg("--cache-dir").arg(&cache_dir);
+    cmd.args([
+        "--username", "oauth2",
+        "--password", "",
+        "--skip-download",
+        "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+    ]);
+    
+    // The device-code flow waits for the user for minutes, so run it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut child = cmd
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start OAuth login: {}", e))?;
+        
+        let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+        let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+        
+        // The plugin prints the device code on either stream depending on yt-dlp version
+        let readers: Vec<Box<dyn std::io::Read + Send>> = vec![Box::new(stdout), Box::new(stderr)];
+        let handles: Vec<_> = readers
+            .into_iter()
+            .map(|stream| {
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    use std::io::{BufRead, BufReader};
+                    
+                    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                        eprintln!("yt-dlp: {}", line);
+                        if let Some((url, code)) = parse_oauth_prompt(&line) {
+                            let emit_result = app.emit("oauth-device-code", serde_json::json!({
+                                "url": url,
+                                "code": code
+                            }));
+                            if let Err(e) = emit_result {
+                                eprintln!("Failed to emit oauth-device-code: {}", e);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        
+        // The user has to enter the code in a browser, so give up if that doesn't happen in time
+        let started = std::time::Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| format!("OAuth login failed: {}", e))? {
+                break Some(status);
+            }
+            if started.elapsed() >= OAUTH_LOGIN_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        };
+        for handle in handles {
+            let _ = handle.join();
+        }
+        
+        match status {
+            Some(status) if status.success() => Ok(()),
+            Some(_) => Err("OAuth login failed or was not completed".to_string()),
+            None => Err(format!(
+                "OAuth login timed out after {} minutes without the code being entered",
+                OAUTH_LOGIN_TIMEOUT.as_secs() / 60
+            )),
+        }
+    })
+    .await
+    .map_err(|e| format!("OAuth login failed: {}", e))??;
     
-    fs::remove_file(&path).map_err(|e| format!("Failed to remove credentials: {}", e))?;
+    let (access_token, refresh_token, expires) = read_oauth_cache_tokens()?;
+    let cookies = read_credentials().map(|c| c.cookies).unwrap_or_default();
+    let credentials = Credentials {
+        access_token,
+        refresh_token,
+        cookies,
+        expires,
+    };
     
-    Ok(())
+    save_credentials(credentials.clone()).await?;
+    
+    Ok(credentials)
 }
 
 // Test function to verify yt-dlp path resolution
@@ -815,7 +6968,7 @@ pub async fn get_recent_downloads() -> Result<Vec<RecentDownload>, String> {
 
 // Save a recent download
 #[tauri::command]
-pub async fn save_recent_download(download: RecentDownload) -> Result<(), String> {
+pub async fn save_recent_download(mut download: RecentDownload) -> Result<(), String> {
     let path = get_recent_downloads_path()?;
     
     // Load existing downloads
@@ -827,11 +6980,37 @@ pub async fn save_recent_download(download: RecentDownload) -> Result<(), String
         Vec::new()
     };
     
+    // Record what was actually written rather than what was requested
+    if std::path::Path::new(&download.file_path).exists() {
+        let file_path = download.file_path.clone();
+        let probe_result = tauri::async_runtime::spawn_blocking(move || probe_media(&file_path))
+            .await
+            .map_err(|e| format!("Failed to probe download: {}", e))?;
+        match probe_result {
+            Ok(probe) => {
+                if !probe.container.is_empty() {
+                    download.format = probe.container.clone();
+                }
+                if let Some(height) = probe.height {
+                    download.quality = format!("{}p", height);
+                }
+                if download.duration == 0 {
+                    download.duration = probe.duration.unwrap_or_default() as u64;
+                }
+                download.probe = Some(probe);
+            }
+            Err(e) => eprintln!("Failed to probe {}: {}", download.file_path, e),
+        }
+    }
+    
     // Add new download at the beginning
     downloads.insert(0, download);
     
-    // Keep only the last 100 downloads
-    downloads.truncate(100);
+    // Keep only the configured number of downloads (0 = unlimited)
+    let max_items = read_settings().max_history_items;
+    if max_items > 0 {
+        downloads.truncate(max_items);
+    }
     
     // Save back to file
     let json = serde_json::to_string_pretty(&downloads)
@@ -921,6 +7100,102 @@ pub async fn open_file_with(path: String, app_path: Option<String>) -> Result<()
     Ok(())
 }
 
+// Helper function to find where a known media player ("vlc", "mpv", "quicktime", "iina") is installed.
+// Returns an .app bundle on macOS, otherwise the player's executable.
+fn find_player(player: &str) -> Option<String> {
+    let candidates: &[&str] = if cfg!(target_os = "macos") {
+        match player {
+            "vlc" => &["/Applications/VLC.app"],
+            "mpv" => &["/Applications/mpv.app", "/opt/homebrew/bin/mpv", "/usr/local/bin/mpv"],
+            "quicktime" => &["/System/Applications/QuickTime Player.app", "/Applications/QuickTime Player.app"],
+            "iina" => &["/Applications/IINA.app"],
+            _ => &[],
+        }
+    } else if cfg!(target_os = "windows") {
+        match player {
+            "vlc" => &[r"C:\Program Files\VideoLAN\VLC\vlc.exe", r"C:\Program Files (x86)\VideoLAN\VLC\vlc.exe"],
+            "mpv" => &[r"C:\Program Files\mpv\mpv.exe"],
+            "quicktime" => &[r"C:\Program Files (x86)\QuickTime\QuickTimePlayer.exe"],
+            _ => &[],
+        }
+    } else {
+        match player {
+            "vlc" => &["/usr/bin/vlc", "/snap/bin/vlc"],
+            "mpv" => &["/usr/bin/mpv", "/snap/bin/mpv"],
+            _ => &[],
+        }
+    };
+    
+    for path in candidates {
+        if std::path::Path::new(path).exists() {
+            return Some(path.to_string());
+        }
+    }
+    
+    // Try to find command-line players using 'which' command
+    if !cfg!(target_os = "windows") && matches!(player, "vlc" | "mpv") {
+        if let Ok(output) = Command::new("which").arg(player).output() {
+            if output.status.success() {
+                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !path.is_empty() {
+                    return Some(path);
+                }
+            }
+        }
+    }
+    
+    None
+}
+
+// Open a file in a known media player ("vlc", "mpv", "quicktime", or "iina")
+#[tauri::command]
+pub async fn open_with_player(path: String, player: String) -> Result<(), String> {
+    let player = player.to_lowercase();
+    let name = match player.as_str() {
+        "vlc" => "VLC",
+        "mpv" => "mpv",
+        "quicktime" => "QuickTime Player",
+        "iina" => "IINA",
+        _ => return Err(format!("Unknown player: {} (expected vlc, mpv, quicktime, or iina)", player)),
+    };
+    let player_path = find_player(&player).ok_or(format!("{} is not installed", name))?;
+    
+    if player_path.ends_with(".app") {
+        Command::new("open")
+            .args(["-a", &player_path, &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open file with {}: {}", name, e))?;
+    } else {
+        Command::new(&player_path)
+            .arg(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to open file with {}: {}", name, e))?;
+    }
+    
+    Ok(())
+}
+
+// Save where playback of a file stopped, so watching can resume there
+#[tauri::command]
+pub async fn save_playback_position(file_path: String, seconds: f64) -> Result<(), String> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(format!("Invalid playback position: {}", seconds));
+    }
+    if !std::path::Path::new(&file_path).exists() {
+        return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, file_path));
+    }
+    
+    let mut positions = read_playback_positions();
+    positions.insert(file_path, seconds);
+    write_playback_positions(&positions)
+}
+
+// Get the saved playback position of a file in seconds, if any
+#[tauri::command]
+pub async fn get_playback_position(file_path: String) -> Result<Option<f64>, String> {
+    Ok(read_playback_positions().get(&file_path).copied())
+}
+
 // Get list of apps that can open a file type
 #[tauri::command]
 pub async fn get_apps_for_file(path: String) -> Result<Vec<(String, String, String)>, String> {
@@ -1105,39 +7380,713 @@ pub async fn open_in_folder(path: String) -> Result<(), String> {
             .map_err(|e| format!("Failed to open folder: {}", e))?;
     }
     
-    Ok(())
+    Ok(())
+}
+
+// Open a recent download's containing folder by history ID
+#[tauri::command]
+pub async fn open_download_in_folder(id: String) -> Result<(), String> {
+    let downloads = get_recent_downloads().await?;
+    let download = downloads
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or_else(|| "Download not found in history".to_string())?;
+    
+    // Report a missing file distinctly so the frontend can offer to prune the entry
+    if !std::path::Path::new(&download.file_path).exists() {
+        return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, download.file_path));
+    }
+    
+    open_in_folder(download.file_path).await
+}
+
+// Delete file
+#[tauri::command]
+pub async fn delete_file(path: String) -> Result<(), String> {
+    // Delete the main file
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
+    
+    // Also delete other files from the same download (e.g. audio extracted alongside the video)
+    let history = get_recent_downloads().await.unwrap_or_default();
+    if let Some(download) = history.iter().find(|d| d.file_path == path) {
+        for extra_file in &download.extra_files {
+            let _ = fs::remove_file(extra_file); // Ignore errors for extra files
+        }
+    }
+    
+    // Also delete associated subtitle files
+    let path_obj = PathBuf::from(&path);
+    if let Some(stem) = path_obj.file_stem() {
+        if let Some(parent) = path_obj.parent() {
+            let stem_str = stem.to_string_lossy();
+            
+            // Common subtitle extensions
+            let subtitle_extensions = ["srt", "vtt", "ass", "sub", "ssa"];
+            // Common language suffixes
+            let lang_suffixes = ["", ".en", ".eng", ".en-orig"];
+            
+            for ext in &subtitle_extensions {
+                for suffix in &lang_suffixes {
+                    let sub_filename = format!("{}{}.{}", stem_str, suffix, ext);
+                    let sub_path = parent.join(&sub_filename);
+                    if sub_path.exists() {
+                        let _ = fs::remove_file(&sub_path); // Ignore errors for subtitle deletion
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+// A leftover partial download found on disk
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PartialDownload {
+    // Final file path the partial download was being written to
+    pub target_path: String,
+    // The .part/.ytdl files belonging to it
+    pub files: Vec<String>,
+    pub size: u64,
+    pub title: String,
+    // Source URL, when the title matches a known recent download
+    pub url: Option<String>,
+}
+
+// Helper function to derive the final output path and a best-guess title from a partial file name
+// Example: "/dl/Some Title.f137.mp4.part" -> ("/dl/Some Title.mp4", "Some Title")
+fn parse_partial_file_name(path: &std::path::Path) -> Option<(PathBuf, String)> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let base = file_name
+        .strip_suffix(".part")
+        .or_else(|| file_name.strip_suffix(".ytdl"))?;
+    
+    let format_suffix = regex::Regex::new(r"\.f\d+(\.[^.]+)$").ok()?;
+    let target = format_suffix.replace(base, "$1").to_string();
+    let title = std::path::Path::new(&target).file_stem()?.to_string_lossy().to_string();
+    
+    Some((path.with_file_name(target), title))
+}
+
+// Scan the save location and the app's temp dir for partial downloads left behind by a crash
+#[tauri::command]
+pub async fn recover_partial_downloads(location: Option<String>) -> Result<Vec<PartialDownload>, String> {
+    let location = match location {
+        Some(location) => location,
+        None => get_default_save_location().await?,
+    };
+    let history = get_recent_downloads().await.unwrap_or_default();
+    
+    let mut partials: Vec<PartialDownload> = Vec::new();
+    for dir in [Some(PathBuf::from(location)), get_temp_dir().ok()].into_iter().flatten() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some((target_path, title)) = parse_partial_file_name(&path) else {
+                continue;
+            };
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let target_path = target_path.to_string_lossy().to_string();
+            
+            if let Some(existing) = partials.iter_mut().find(|p| p.target_path == target_path) {
+                existing.files.push(path.to_string_lossy().to_string());
+                existing.size += size;
+                continue;
+            }
+            
+            let url = history
+                .iter()
+                .find(|d| d.title == title || d.file_path == target_path)
+                .map(|d| d.url.clone());
+            
+            partials.push(PartialDownload {
+                target_path,
+                files: vec![path.to_string_lossy().to_string()],
+                size,
+                title,
+                url,
+            });
+        }
+    }
+    
+    Ok(partials)
+}
+
+// A chapter of a video, with times in seconds
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Chapter {
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+// Helper function to parse the chapters array from yt-dlp JSON
+fn parse_chapters(json: &serde_json::Value) -> Vec<Chapter> {
+    json["chapters"].as_array()
+        .map(|chapters| chapters.iter().filter_map(|c| Some(Chapter {
+            title: c["title"].as_str()?.to_string(),
+            start_time: c["start_time"].as_f64()?,
+            end_time: c["end_time"].as_f64()?,
+        })).collect())
+        .unwrap_or_default()
+}
+
+// Download only the chapter whose title matches (case-insensitively). Returns the download ID,
+// or an error listing the video's chapter titles when none match.
+#[tauri::command]
+pub async fn download_chapter(
+    url: String,
+    chapter: String,
+    output: String,
+    app: AppHandle,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    let json = fetch_video_json_checked_async(&url, &app).await?;
+    let chapters = parse_chapters(&json);
+    if chapters.is_empty() {
+        return Err("This video has no chapters".to_string());
+    }
+    
+    let wanted = chapter.trim().to_lowercase();
+    let Some(found) = chapters.iter().find(|c| c.title.trim().to_lowercase() == wanted) else {
+        let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+        return Err(format!("No chapter named \"{}\". Available chapters: {}", chapter, titles.join(", ")));
+    };
+    
+    let options = DownloadOptions {
+        url,
+        output,
+        title: Some(found.title.clone()),
+        download_section: Some(format!("*{}-{}", found.start_time, found.end_time)),
+        ..Default::default()
+    };
+    
+    start_download(options, app, manager).await
+}
+
+// Resume a partial download; yt-dlp continues from the existing .part file
+#[tauri::command]
+pub async fn resume_partial_download(
+    target_path: String,
+    url: String,
+    app: AppHandle,
+    manager: State<'_, DownloadManager>,
+) -> Result<String, String> {
+    let options = DownloadOptions {
+        url,
+        output: target_path,
+        ..Default::default()
+    };
+    
+    start_download(options, app, manager).await
+}
+
+// Helper function to update a history entry's title, thumbnail, and duration from yt-dlp.
+// The file path and other download details are left untouched.
+fn refresh_entry_metadata(download: &mut RecentDownload) -> Result<(), String> {
+    let json = fetch_video_json(&download.url)?;
+    
+    if let Some(title) = json["title"].as_str() {
+        download.title = title.to_string();
+    }
+    if let Some(thumbnail) = json["thumbnail"].as_str() {
+        download.thumbnail = thumbnail.to_string();
+    }
+    if let Some(duration) = json["duration"].as_f64() {
+        download.duration = duration as u64;
+    }
+    if let Some(uploader) = json["uploader"].as_str() {
+        download.uploader = uploader.to_string();
+    }
+    
+    Ok(())
+}
+
+// Re-fetch current metadata for a single history entry
+#[tauri::command]
+pub async fn refresh_history_entry(id: String) -> Result<RecentDownload, String> {
+    let mut downloads = read_recent_downloads()?;
+    let download = downloads.iter_mut()
+        .find(|d| d.id == id)
+        .ok_or(format!("Recent download not found: {}", id))?;
+    
+    // Waits for an info fetch slot, so run it off the async runtime
+    let mut entry = download.clone();
+    let refreshed = tauri::async_runtime::spawn_blocking(move || {
+        refresh_entry_metadata(&mut entry).map(|_| entry)
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh history entry: {}", e))??;
+    *download = refreshed.clone();
+    write_recent_downloads(&downloads)?;
+    
+    Ok(refreshed)
+}
+
+// A history entry that could not be refreshed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryRefreshFailure {
+    pub id: String,
+    pub url: String,
+    pub error: String,
+}
+
+// Outcome of refreshing the whole history
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryRefreshReport {
+    pub updated: Vec<String>,
+    pub unavailable: Vec<HistoryRefreshFailure>,
+}
+
+// Re-fetch current metadata for every history entry, pausing between requests
+// so large histories don't trip YouTube's rate limiting
+#[tauri::command]
+pub async fn refresh_all_history(app: AppHandle) -> Result<HistoryRefreshReport, String> {
+    let mut downloads = read_recent_downloads()?;
+    let total = downloads.len();
+    
+    // The fetches and the pauses between them run off the async runtime
+    let (downloads, report) = tauri::async_runtime::spawn_blocking(move || {
+        let mut report = HistoryRefreshReport {
+            updated: Vec::new(),
+            unavailable: Vec::new(),
+        };
+        
+        for (index, download) in downloads.iter_mut().enumerate() {
+            if index > 0 {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+            }
+            
+            // Entries whose video is gone keep their old metadata and are reported back
+            match refresh_entry_metadata(download) {
+                Ok(()) => report.updated.push(download.id.clone()),
+                Err(e) => report.unavailable.push(HistoryRefreshFailure {
+                    id: download.id.clone(),
+                    url: download.url.clone(),
+                    error: e,
+                }),
+            }
+            
+            let emit_result = app.emit("history-refresh-progress", serde_json::json!({
+                "completed": index + 1,
+                "total": total
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit history-refresh-progress: {}", e);
+            }
+        }
+        (downloads, report)
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh history: {}", e))?;
+    
+    // Re-read before writing so downloads saved during the refresh aren't lost
+    let mut current = read_recent_downloads()?;
+    for entry in current.iter_mut() {
+        if let Some(refreshed) = downloads.iter().find(|d| d.id == entry.id) {
+            entry.title = refreshed.title.clone();
+            entry.thumbnail = refreshed.thumbnail.clone();
+            entry.duration = refreshed.duration;
+            entry.uploader = refreshed.uploader.clone();
+        }
+    }
+    write_recent_downloads(&current)?;
+    
+    Ok(report)
+}
+
+// Helper function to escape text for an XML element
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Helper function to build Kodi/Jellyfin .nfo XML from yt-dlp JSON
+fn build_nfo(json: &serde_json::Value, episode: bool) -> String {
+    let root = if episode { "episodedetails" } else { "movie" };
+    let text = |key: &str| escape_xml(json[key].as_str().unwrap_or(""));
+    
+    // upload_date is "YYYYMMDD"; .nfo expects "YYYY-MM-DD"
+    let premiered = json["upload_date"].as_str()
+        .filter(|date| date.len() == 8)
+        .map(|date| format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))
+        .unwrap_or_default();
+    // Runtime is in whole minutes
+    let runtime = json["duration"].as_f64().map(|secs| (secs / 60.0).round() as u64).unwrap_or(0);
+    
+    let mut nfo = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    nfo.push_str(&format!("<{}>\n", root));
+    nfo.push_str(&format!("  <title>{}</title>\n", text("title")));
+    nfo.push_str(&format!("  <plot>{}</plot>\n", text("description")));
+    nfo.push_str(&format!("  <premiered>{}</premiered>\n", premiered));
+    nfo.push_str(&format!("  <studio>{}</studio>\n", text("uploader")));
+    nfo.push_str(&format!("  <runtime>{}</runtime>\n", runtime));
+    nfo.push_str(&format!("  <thumb>{}</thumb>\n", text("thumbnail")));
+    nfo.push_str(&format!("  <uniqueid type=\"youtube\" default=\"true\">{}</uniqueid>\n", text("id")));
+    nfo.push_str(&format!("</{}>\n", root));
+    nfo
+}
+
+// Write a Kodi/Jellyfin .nfo file next to a video. `item` is a recent download ID, or a URL
+// together with the video_path to write next to. With episode, an <episodedetails> file is
+// written instead of <movie>. Returns the .nfo path.
+#[tauri::command]
+pub async fn export_nfo(
+    item: String,
+    video_path: Option<String>,
+    episode: Option<bool>,
+) -> Result<String, String> {
+    let downloads = read_recent_downloads()?;
+    let (url, video_path) = match downloads.iter().find(|d| d.id == item) {
+        Some(download) => (download.url.clone(), video_path.unwrap_or_else(|| download.file_path.clone())),
+        None => (item, video_path.ok_or("A video path is required when exporting from a URL")?),
+    };
+    
+    let json = fetch_video_json_async(&url).await?;
+    let nfo_path = std::path::Path::new(&video_path).with_extension("nfo");
+    fs::write(&nfo_path, build_nfo(&json, episode.unwrap_or(false)))
+        .map_err(|e| format!("Failed to write .nfo file: {}", e))?;
+    
+    Ok(nfo_path.to_string_lossy().to_string())
+}
+
+// Write an m3u8 or pls playlist of downloaded files, in the order of the given history IDs.
+// Entries whose file no longer exists are left out. Returns the number of tracks written.
+#[tauri::command]
+pub async fn generate_playlist_file(
+    ids: Vec<String>,
+    output_path: String,
+    format: String,
+) -> Result<usize, String> {
+    if format != "m3u8" && format != "pls" {
+        return Err(format!("Unsupported playlist format: {} (expected m3u8 or pls)", format));
+    }
+    
+    let downloads = read_recent_downloads()?;
+    let tracks: Vec<&RecentDownload> = ids.iter()
+        .filter_map(|id| downloads.iter().find(|d| &d.id == id))
+        .filter(|d| std::path::Path::new(&d.file_path).exists())
+        .collect();
+    if tracks.is_empty() {
+        return Err("None of the selected downloads exist on disk".to_string());
+    }
+    
+    let mut content = String::new();
+    if format == "m3u8" {
+        content.push_str("#EXTM3U\n");
+        for track in &tracks {
+            content.push_str(&format!("#EXTINF:{},{}\n{}\n", track.duration, track.title, track.file_path));
+        }
+    } else {
+        content.push_str("[playlist]\n");
+        for (index, track) in tracks.iter().enumerate() {
+            let n = index + 1;
+            content.push_str(&format!("File{}={}\nTitle{}={}\nLength{}={}\n", n, track.file_path, n, track.title, n, track.duration));
+        }
+        content.push_str(&format!("NumberOfEntries={}\nVersion=2\n", tracks.len()));
+    }
+    
+    fs::write(&output_path, content)
+        .map_err(|e| format!("Failed to write playlist: {}", e))?;
+    
+    Ok(tracks.len())
+}
+
+// Helper function to fingerprint a file by hashing its size plus the first and last MiB.
+// Much faster than a full hash for large videos while still telling different videos apart.
+fn quick_file_hash(path: &str) -> Result<String, String> {
+    use std::io::{Read, Seek, SeekFrom};
+    const CHUNK: u64 = 1024 * 1024;
+    
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let size = file.metadata().map_err(|e| format!("Failed to get file metadata: {}", e))?.len();
+    
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&size.to_le_bytes());
+    
+    let mut buffer = vec![0u8; CHUNK.min(size) as usize];
+    file.read_exact(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    hasher.update(&buffer);
+    
+    if size > CHUNK {
+        let tail = CHUNK.min(size - CHUNK);
+        file.seek(SeekFrom::End(-(tail as i64))).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        buffer.resize(tail as usize, 0);
+        file.read_exact(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        hasher.update(&buffer);
+    }
+    
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+// Find downloaded files that are identical, returning groups of duplicate paths.
+// Only reports; nothing is deleted.
+#[tauri::command]
+pub async fn find_duplicate_downloads(app: AppHandle) -> Result<Vec<Vec<String>>, String> {
+    let downloads = read_recent_downloads()?;
+    
+    tauri::async_runtime::spawn_blocking(move || {
+        // Unique existing files referenced by history, grouped by size first;
+        // files with a unique size can't have a duplicate and are never hashed
+        let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+        let mut seen = HashSet::new();
+        for path in downloads.iter().flat_map(|d| std::iter::once(&d.file_path).chain(&d.extra_files)) {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            if let Ok(metadata) = fs::metadata(path) {
+                if metadata.is_file() {
+                    by_size.entry(metadata.len()).or_default().push(path.clone());
+                }
+            }
+        }
+        let candidates: Vec<String> = by_size.into_values()
+            .filter(|paths| paths.len() > 1)
+            .flatten()
+            .collect();
+        
+        let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+        for (index, path) in candidates.iter().enumerate() {
+            match quick_file_hash(path) {
+                Ok(hash) => by_hash.entry(hash).or_default().push(path.clone()),
+                Err(e) => eprintln!("Skipping {}: {}", path, e),
+            }
+            
+            let emit_result = app.emit("duplicate-scan-progress", serde_json::json!({
+                "completed": index + 1,
+                "total": candidates.len()
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit duplicate-scan-progress: {}", e);
+            }
+        }
+        
+        by_hash.into_values().filter(|paths| paths.len() > 1).collect()
+    })
+    .await
+    .map_err(|e| format!("Duplicate scan failed: {}", e))
+}
+
+// Size and SHA-256 of one file in a checksum manifest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecksumEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+// A written checksum manifest and the files it covers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecksumManifest {
+    pub manifest_path: String,
+    pub entries: Vec<ChecksumEntry>,
+}
+
+// A file whose current hash doesn't match the manifest (actual is None if it couldn't be read)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecksumMismatch {
+    pub path: String,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub error: Option<String>,
+}
+
+// Outcome of verifying a checksum manifest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecksumVerification {
+    pub verified: usize,
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+// Helper function to SHA-256 a file in 1 MiB chunks, emitting checksum-progress as it goes
+fn sha256_file(app: &AppHandle, path: &str) -> Result<(u64, String), String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+    
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let size = file.metadata().map_err(|e| format!("Failed to get file metadata: {}", e))?.len();
+    
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut hashed = 0u64;
+    let mut last_percent = 0;
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        hashed += read as u64;
+        
+        // Report each whole percent rather than every chunk
+        let percent = (hashed * 100).checked_div(size).unwrap_or(100);
+        if percent > last_percent {
+            last_percent = percent;
+            let emit_result = app.emit("checksum-progress", serde_json::json!({
+                "path": path,
+                "hashed_bytes": hashed,
+                "total_bytes": size,
+                "progress": percent
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit checksum-progress: {}", e);
+            }
+        }
+    }
+    
+    Ok((size, format!("{:x}", hasher.finalize())))
 }
 
-// Delete file
+// Compute the size and SHA-256 of files (given as paths or history IDs) and write them
+// to a sha256sum-compatible manifest. Defaults to "checksums.sha256" next to the first file.
 #[tauri::command]
-pub async fn delete_file(path: String) -> Result<(), String> {
-    // Delete the main file
-    fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
+pub async fn generate_checksums(
+    items: Vec<String>,
+    manifest_path: Option<String>,
+    app: AppHandle,
+) -> Result<ChecksumManifest, String> {
+    let downloads = read_recent_downloads()?;
+    let paths: Vec<String> = items.iter()
+        .map(|item| downloads.iter()
+            .find(|d| &d.id == item)
+            .map(|d| d.file_path.clone())
+            .unwrap_or_else(|| item.clone()))
+        .collect();
+    if paths.is_empty() {
+        return Err("No files to checksum".to_string());
+    }
+    for path in &paths {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, path));
+        }
+    }
     
-    // Also delete associated subtitle files
-    let path_obj = PathBuf::from(&path);
-    if let Some(stem) = path_obj.file_stem() {
-        if let Some(parent) = path_obj.parent() {
-            let stem_str = stem.to_string_lossy();
-            
-            // Common subtitle extensions
-            let subtitle_extensions = ["srt", "vtt", "ass", "sub", "ssa"];
-            // Common language suffixes
-            let lang_suffixes = ["", ".en", ".eng", ".en-orig"];
-            
-            for ext in &subtitle_extensions {
-                for suffix in &lang_suffixes {
-                    let sub_filename = format!("{}{}.{}", stem_str, suffix, ext);
-                    let sub_path = parent.join(&sub_filename);
-                    if sub_path.exists() {
-                        let _ = fs::remove_file(&sub_path); // Ignore errors for subtitle deletion
-                    }
-                }
+    let manifest_path = match manifest_path {
+        Some(path) => PathBuf::from(path),
+        None => std::path::Path::new(&paths[0])
+            .with_file_name("checksums.sha256"),
+    };
+    
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut entries = Vec::new();
+        let mut content = String::new();
+        for path in paths {
+            let (size, sha256) = sha256_file(&app, &path)?;
+            // Files next to the manifest are listed by name so the folder can be moved as a whole
+            let listed = std::path::Path::new(&path)
+                .strip_prefix(manifest_path.parent().unwrap_or(std::path::Path::new("")))
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.clone());
+            content.push_str(&format!("{}  {}\n", sha256, listed));
+            entries.push(ChecksumEntry { path, size, sha256 });
+        }
+        
+        fs::write(&manifest_path, content)
+            .map_err(|e| format!("Failed to write checksum manifest: {}", e))?;
+        
+        Ok(ChecksumManifest {
+            manifest_path: manifest_path.to_string_lossy().to_string(),
+            entries,
+        })
+    })
+    .await
+    .map_err(|e| format!("Checksum generation failed: {}", e))?
+}
+
+// Re-hash every file listed in a checksum manifest and report the ones that don't match
+#[tauri::command]
+pub async fn verify_checksums(manifest_path: String, app: AppHandle) -> Result<ChecksumVerification, String> {
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read checksum manifest: {}", e))?;
+    let base = std::path::Path::new(&manifest_path).parent().unwrap_or(std::path::Path::new("")).to_path_buf();
+    
+    // Example line: "e3b0c442...b855  Some Title.mp4" (binary-mode lines use " *" instead)
+    let listed: Vec<(String, String)> = content.lines()
+        .filter_map(|line| {
+            let (hash, name) = line.split_once(' ')?;
+            let name = name.strip_prefix(' ').or_else(|| name.strip_prefix('*'))?;
+            Some((hash.to_lowercase(), base.join(name).to_string_lossy().to_string()))
+        })
+        .collect();
+    
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut verification = ChecksumVerification {
+            verified: 0,
+            mismatches: Vec::new(),
+        };
+        for (expected, path) in listed {
+            match sha256_file(&app, &path) {
+                Ok((_, actual)) if actual == expected => verification.verified += 1,
+                Ok((_, actual)) => verification.mismatches.push(ChecksumMismatch {
+                    path,
+                    expected,
+                    actual: Some(actual),
+                    error: None,
+                }),
+                Err(e) => verification.mismatches.push(ChecksumMismatch {
+                    path,
+                    expected,
+                    actual: None,
+                    error: Some(e),
+                }),
             }
         }
+        verification
+    })
+    .await
+    .map_err(|e| format!("Checksum verification failed: {}", e))
+}
+
+// Number of downloads from one uploader
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploaderCount {
+    pub uploader: String,
+    pub count: u64,
+}
+
+// Aggregate statistics over the download history
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DownloadStats {
+    pub count: u64,
+    pub total_bytes: u64,
+    pub total_duration_secs: u64,
+    pub by_format: HashMap<String, u64>,
+    pub by_quality: HashMap<String, u64>,
+    // Most downloaded uploaders first (at most 10)
+    pub top_uploaders: Vec<UploaderCount>,
+}
+
+// Get aggregate statistics over the download history (zeroed if there is none)
+#[tauri::command]
+pub async fn get_download_stats() -> Result<DownloadStats, String> {
+    let mut stats = DownloadStats::default();
+    let mut uploaders: HashMap<String, u64> = HashMap::new();
+    
+    for download in read_recent_downloads()? {
+        stats.count += 1;
+        stats.total_bytes += download.size;
+        stats.total_duration_secs += download.duration;
+        *stats.by_format.entry(download.format).or_default() += 1;
+        *stats.by_quality.entry(download.quality).or_default() += 1;
+        if !download.uploader.is_empty() {
+            *uploaders.entry(download.uploader).or_default() += 1;
+        }
     }
     
-    Ok(())
+    let mut top_uploaders: Vec<UploaderCount> = uploaders.into_iter()
+        .map(|(uploader, count)| UploaderCount { uploader, count })
+        .collect();
+    top_uploaders.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.uploader.cmp(&b.uploader)));
+    top_uploaders.truncate(10);
+    stats.top_uploaders = top_uploaders;
+    
+    Ok(stats)
 }
 
 // Clear recent downloads
@@ -1152,6 +8101,80 @@ pub async fn clear_recent_downloads() -> Result<(), String> {
     Ok(())
 }
 
+// Change the title shown for a recent download; the file itself is left untouched
+#[tauri::command]
+pub async fn rename_history_entry(id: String, new_title: String) -> Result<RecentDownload, String> {
+    let new_title = new_title.trim();
+    if new_title.is_empty() {
+        return Err("Title cannot be empty".to_string());
+    }
+    
+    let mut downloads = read_recent_downloads()?;
+    let download = downloads.iter_mut()
+        .find(|d| d.id == id)
+        .ok_or(format!("Recent download not found: {}", id))?;
+    download.title = new_title.to_string();
+    let renamed = download.clone();
+    write_recent_downloads(&downloads)?;
+    
+    Ok(renamed)
+}
+
+// Rename a recent download's file on disk (keeping its extension) along with its subtitle
+// files, and update the history entry's file path. The displayed title is not changed.
+#[tauri::command]
+pub async fn rename_download_file(id: String, new_name: String) -> Result<RecentDownload, String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name.contains(['/', '\\']) {
+        return Err(format!("Invalid file name: {}", new_name));
+    }
+    
+    let mut downloads = read_recent_downloads()?;
+    let download = downloads.iter_mut()
+        .find(|d| d.id == id)
+        .ok_or(format!("Recent download not found: {}", id))?;
+    
+    let old_path = PathBuf::from(&download.file_path);
+    if !old_path.exists() {
+        return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, download.file_path));
+    }
+    let old_stem = old_path.file_stem().ok_or("Invalid file path")?.to_string_lossy().to_string();
+    let parent = old_path.parent().ok_or("Invalid file path")?.to_path_buf();
+    let new_path = match old_path.extension() {
+        Some(ext) => parent.join(format!("{}.{}", new_name, ext.to_string_lossy())),
+        None => parent.join(new_name),
+    };
+    if new_path.exists() {
+        return Err(format!("A file named {} already exists", new_path.display()));
+    }
+    
+    fs::rename(&old_path, &new_path).map_err(|e| format!("Failed to rename file: {}", e))?;
+    
+    // Subtitle sidecars are named "<stem>.<ext>" or "<stem>.<lang>.<ext>"
+    let subtitle_extensions = ["srt", "vtt", "ass", "sub", "ssa"];
+    let prefix = format!("{}.", old_stem);
+    if let Ok(entries) = fs::read_dir(&parent) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(rest) = file_name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let is_subtitle = std::path::Path::new(&file_name)
+                .extension()
+                .is_some_and(|ext| subtitle_extensions.contains(&ext.to_string_lossy().to_lowercase().as_str()));
+            if is_subtitle {
+                let _ = fs::rename(entry.path(), parent.join(format!("{}.{}", new_name, rest))); // Ignore errors for subtitles
+            }
+        }
+    }
+    
+    download.file_path = new_path.to_string_lossy().to_string();
+    let renamed = download.clone();
+    write_recent_downloads(&downloads)?;
+    
+    Ok(renamed)
+}
+
 // Remove a single recent download by ID
 #[tauri::command]
 pub async fn remove_recent_download(id: String) -> Result<(), String> {
@@ -1180,6 +8203,99 @@ pub async fn remove_recent_download(id: String) -> Result<(), String> {
     Ok(())
 }
 
+// Outcome of deleting one history entry in delete_downloads
+#[derive(Debug, Serialize, Clone)]
+pub struct DeleteDownloadResult {
+    pub id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// Helper function to find the sidecars of a downloaded file: subtitles named "<stem>.<ext>"
+// or "<stem>.<lang>.<ext>", and a thumbnail or .nfo named "<stem>.<ext>"
+fn find_sidecar_files(file_path: &str) -> Vec<PathBuf> {
+    let subtitle_extensions = ["srt", "vtt", "ass", "sub", "ssa"];
+    let other_extensions = ["jpg", "webp", "png", "nfo"];
+    let path = std::path::Path::new(file_path);
+    let (Some(stem), Some(parent)) = (path.file_stem(), path.parent()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", stem.to_string_lossy());
+    
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|candidate| candidate.as_path() != path)
+        .filter(|candidate| {
+            let file_name = candidate.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let Some(rest) = file_name.strip_prefix(&prefix) else {
+                return false;
+            };
+            let ext = candidate.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+            subtitle_extensions.contains(&ext.as_str())
+                || (other_extensions.contains(&ext.as_str()) && !rest.contains('.'))
+        })
+        .collect()
+}
+
+// Remove several history entries with a single write, optionally moving their files
+// (and sidecars) to the trash. Entries whose file couldn't be trashed stay in the history.
+#[tauri::command]
+pub async fn delete_downloads(ids: Vec<String>, delete_files: bool) -> Result<Vec<DeleteDownloadResult>, String> {
+    let mut downloads = read_recent_downloads()?;
+    let mut removed = HashSet::new();
+    let mut results = Vec::new();
+    
+    for id in ids {
+        let Some(download) = downloads.iter().find(|d| d.id == id) else {
+            results.push(DeleteDownloadResult {
+                error: Some(format!("Recent download not found: {}", id)),
+                id,
+                success: false,
+            });
+            continue;
+        };
+        
+        if delete_files {
+            // A file that is already gone only needs its history entry removed
+            let main_file = std::path::Path::new(&download.file_path);
+            if main_file.exists() {
+                if let Err(e) = trash::delete(main_file) {
+                    results.push(DeleteDownloadResult {
+                        error: Some(format!("Failed to move file to trash: {}", e)),
+                        id,
+                        success: false,
+                    });
+                    continue;
+                }
+            }
+            
+            let extra_files = download.extra_files.iter().map(PathBuf::from);
+            for file in extra_files.chain(find_sidecar_files(&download.file_path)) {
+                if file.exists() {
+                    let _ = trash::delete(&file); // Ignore errors for extra files and sidecars
+                }
+            }
+        }
+        
+        removed.insert(id.clone());
+        results.push(DeleteDownloadResult {
+            id,
+            success: true,
+            error: None,
+        });
+    }
+    
+    if !removed.is_empty() {
+        downloads.retain(|d| !removed.contains(&d.id));
+        write_recent_downloads(&downloads)?;
+    }
+    
+    Ok(results)
+}
+
 // Get file size
 #[tauri::command]
 pub async fn get_file_size(path: String) -> Result<u64, String> {
@@ -1188,6 +8304,124 @@ pub async fn get_file_size(path: String) -> Result<u64, String> {
     Ok(metadata.len())
 }
 
+// Probe a local file for its real container, codecs, resolution, fps, duration and bitrate
+#[tauri::command]
+pub async fn probe_file(path: String) -> Result<MediaProbe, String> {
+    if !std::path::Path::new(&path).exists() {
+        return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, path));
+    }
+    tauri::async_runtime::spawn_blocking(move || probe_media(&path))
+        .await
+        .map_err(|e| format!("Failed to probe file: {}", e))?
+}
+
+// Result of re-encoding a video
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TranscodeResult {
+    pub output_path: String,
+    pub original_size: u64,
+    pub new_size: u64,
+    // Bytes saved (negative if the new file is larger)
+    pub size_reduction: i64,
+}
+
+// Re-encode a video to a different codec and/or resolution with ffmpeg
+#[tauri::command]
+pub async fn transcode_video(
+    input: String,
+    codec: String,
+    resolution: Option<String>,
+    bitrate: Option<String>,
+    crf: Option<u32>,
+    app: AppHandle,
+) -> Result<TranscodeResult, String> {
+    let ffmpeg_path = find_ffmpeg().ok_or("ffmpeg is required to transcode videos")?;
+    
+    let (encoder, audio_encoder, ext) = match codec.as_str() {
+        "h264" => ("libx264", "aac", "mp4"),
+        "h265" => ("libx265", "aac", "mp4"),
+        "vp9" => ("libvpx-vp9", "libopus", "webm"),
+        _ => return Err(format!("Unsupported codec: {} (expected h264, h265, or vp9)", codec)),
+    };
+    
+    // Accept "1280x720", "720p", or "720"
+    let scale = match resolution.as_deref() {
+        None => None,
+        Some(res) => {
+            let res = res.trim_end_matches('p');
+            let filter = match res.split_once('x') {
+                Some((w, h)) if w.parse::<u32>().is_ok() && h.parse::<u32>().is_ok() => format!("scale={}:{}", w, h),
+                None if res.parse::<u32>().is_ok() => format!("scale=-2:{}", res),
+                _ => return Err(format!("Invalid resolution: {}", res)),
+            };
+            Some(filter)
+        }
+    };
+    
+    // Probing and re-encoding can take minutes, so keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        // Make sure this ffmpeg build actually includes the encoder
+        let encoders = Command::new(&ffmpeg_path)
+            .args(["-hide_banner", "-encoders"])
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+        let has_encoder = String::from_utf8_lossy(&encoders.stdout)
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(encoder));
+        if !has_encoder {
+            return Err(format!("Your ffmpeg build does not include the {} encoder needed for {}", encoder, codec));
+        }
+        
+        // ffmpeg runs with -y, so number the name past any existing file instead of replacing it
+        let input_path = std::path::Path::new(&input);
+        let stem = input_path.file_stem().ok_or("Invalid input path")?.to_string_lossy().to_string();
+        let output_path = (0..)
+            .map(|n| {
+                let name = match n {
+                    0 => format!("{}-{}.{}", stem, codec, ext),
+                    n => format!("{}-{} ({}).{}", stem, codec, n, ext),
+                };
+                input_path.with_file_name(name)
+            })
+            .find(|path| !path.exists())
+            .ok_or("Failed to find a free output file name")?
+            .to_string_lossy()
+            .to_string();
+        
+        let mut args = vec!["-i".to_string(), input.clone(), "-c:v".to_string(), encoder.to_string()];
+        if let Some(filter) = scale {
+            args.extend(["-vf".to_string(), filter]);
+        }
+        if let Some(bitrate) = bitrate {
+            args.extend(["-b:v".to_string(), bitrate]);
+        } else {
+            // Constant quality; vp9 needs -b:v 0 for CRF mode
+            args.extend(["-crf".to_string(), crf.unwrap_or(23).to_string()]);
+            if codec == "vp9" {
+                args.extend(["-b:v".to_string(), "0".to_string()]);
+            }
+        }
+        args.extend(["-c:a".to_string(), audio_encoder.to_string(), output_path.clone()]);
+        
+        let duration = get_media_duration(&input).unwrap_or(0.0);
+        run_ffmpeg_with_progress(&app, "transcode-progress", &input, &args, duration)?;
+        
+        let original_size = fs::metadata(&input).map(|m| m.len()).unwrap_or(0);
+        let new_size = fs::metadata(&output_path)
+            .map_err(|e| format!("Failed to read transcoded file: {}", e))?
+            .len();
+        
+        Ok(TranscodeResult {
+            output_path,
+            original_size,
+            new_size,
+            size_reduction: original_size as i64 - new_size as i64,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to transcode video: {}", e))?
+}
+
 // Refresh cookies from browser
 #[tauri::command]
 pub async fn refresh_cookies(browser: Option<String>) -> Result<(), String> {
@@ -1206,8 +8440,11 @@ pub async fn refresh_cookies(browser: Option<String>) -> Result<(), String> {
     // Default to Chrome if no browser specified
     let browser_name = browser.unwrap_or_else(|| "chrome".to_string());
     
+    let settings = read_settings();
     let mut cmd = Command::new(&yt_dlp_info.path);
     configure_command_env(&mut cmd, &yt_dlp_info);
+    apply_extractor_args(&mut cmd, &settings.extractor_args);
+    apply_http_headers(&mut cmd, settings.user_agent.as_deref(), settings.referer.as_deref());
     
     let output = cmd
         .args([
@@ -1227,13 +8464,190 @@ pub async fn refresh_cookies(browser: Option<String>) -> Result<(), String> {
     Ok(())
 }
 
-// Check if error indicates expired/invalid cookies
-fn is_cookie_error(error: &str) -> bool {
+// Start the background timer that refreshes cookies from the configured browser.
+// Settings are re-read every minute so toggling auto refresh takes effect without a restart.
+pub fn start_cookie_refresh_timer(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_refresh = std::time::Instant::now();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(60));
+            if *app.state::<DownloadManager>().shutting_down.lock().unwrap() {
+                break;
+            }
+            
+            let settings = read_settings();
+            let interval = std::time::Duration::from_secs(settings.cookie_refresh_interval_hours * 3600);
+            if !settings.enable_auto_cookie_refresh || last_refresh.elapsed() < interval {
+                continue;
+            }
+            last_refresh = std::time::Instant::now();
+            
+            let browser = settings.cookie_browser.unwrap_or_else(|| "chrome".to_string());
+            match tauri::async_runtime::block_on(refresh_cookies(Some(browser.clone()))) {
+                Ok(()) => {
+                    if let Err(e) = app.emit("cookies-refreshed", serde_json::json!({ "browser": browser })) {
+                        eprintln!("Failed to emit cookies-refreshed: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Automatic cookie refresh failed: {}", e),
+            }
+        }
+    });
+}
+
+// Result of validating a cookies.txt file
+// Pasted cookies written for a single download, readable only by the current user.
+// The file is removed when this is dropped, including when the download's thread panics.
+struct TempCookiesFile {
+    path: PathBuf,
+}
+
+impl TempCookiesFile {
+    fn create(path: PathBuf, content: &str) -> Result<Self, String> {
+        use std::io::Write;
+        
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)
+            .map_err(|e| format!("Failed to write cookies: {}", e))?;
+        // Own the file before writing so a failed write still removes it
+        let cookies = Self { path };
+        file.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write cookies: {}", e))?;
+        Ok(cookies)
+    }
+}
+
+impl Drop for TempCookiesFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Remove pasted cookies files left behind when the app was killed mid-download.
+// Called from setup in main.rs.
+pub fn remove_stale_temp_cookies() {
+    let Ok(entries) = get_temp_dir().and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("cookies-") && name.ends_with(".txt") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CookiesValidation {
+    pub is_valid: bool,
+    pub youtube_cookie_count: u32,
+    // Unix timestamp of the earliest expiring SID/SAPISID cookie
+    pub earliest_expiry: Option<u64>,
+    pub message: String,
+}
+
+// Helper function to check Netscape cookies.txt content
+fn validate_cookies_content(content: &str) -> CookiesValidation {
+    // Session cookies that YouTube needs for authenticated requests
+    let auth_cookie_names = ["SID", "SAPISID", "__Secure-1PSID", "__Secure-3PSID"];
+    
+    let has_header = content.lines().next().is_some_and(|line| {
+        line.starts_with("# Netscape HTTP Cookie File") || line.starts_with("# HTTP Cookie File")
+    });
+    
+    let mut malformed_lines = 0;
+    let mut youtube_cookie_count = 0;
+    let mut earliest_expiry: Option<u64> = None;
+    
+    for line in content.lines() {
+        // "#HttpOnly_" prefixes a real cookie line; other "#" lines are comments
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            malformed_lines += 1;
+            continue;
+        }
+        
+        if !fields[0].ends_with("youtube.com") {
+            continue;
+        }
+        youtube_cookie_count += 1;
+        
+        if auth_cookie_names.contains(&fields[5]) {
+            // Expiry 0 marks a session cookie
+            if let Ok(expiry) = fields[4].parse::<u64>() {
+                if expiry > 0 {
+                    earliest_expiry = Some(earliest_expiry.map_or(expiry, |e| e.min(expiry)));
+                }
+            }
+        }
+    }
+    
+    let (is_valid, message) = if !has_header && malformed_lines > 0 {
+        (false, "Not a Netscape format cookies file".to_string())
+    } else if youtube_cookie_count == 0 {
+        (false, "No youtube.com cookies found".to_string())
+    } else if malformed_lines > 0 {
+        (true, format!("{} malformed lines were ignored", malformed_lines))
+    } else {
+        (true, "Cookies file is valid".to_string())
+    };
+    
+    CookiesValidation {
+        is_valid,
+        youtube_cookie_count,
+        earliest_expiry,
+        message,
+    }
+}
+
+// Validate a cookies.txt file (defaults to the app's cookies file)
+#[tauri::command]
+pub async fn validate_cookies_file(path: Option<String>) -> Result<CookiesValidation, String> {
+    let path = match path {
+        Some(path) => path,
+        None => get_cookies_path()?,
+    };
+    
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read cookies file: {}", e))?;
+    
+    Ok(validate_cookies_content(&content))
+}
+
+// What kind of failure a yt-dlp error represents
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ErrorCategory {
+    // The video needs a channel membership the account doesn't have; new cookies won't help
+    MembersOnly,
+    // Cookies are missing, expired, or invalid
+    CookiesExpired,
+    // YouTube answered HTTP 429; requests need to slow down
+    RateLimited,
+    Other,
+}
+
+// Helper function to classify a yt-dlp error message
+fn classify_error(error: &str) -> ErrorCategory {
+    let members_only_patterns = [
+        "This video is available to this channel's members",
+        "Join this channel to get access",
+        "members-only",
+        "members only",
+    ];
     let cookie_error_patterns = [
         "Sign in to confirm your age",
         "Sign in to confirm you're not a bot",
-        "This video is available to this channel's members",
-        "Join this channel to get access",
         "Private video",
         "Video unavailable",
         "cookies",
@@ -1242,28 +8656,267 @@ fn is_cookie_error(error: &str) -> bool {
         "authentication",
     ];
     
+    let rate_limit_patterns = [
+        "HTTP Error 429",
+        "Too Many Requests",
+    ];
+    
     let error_lower = error.to_lowercase();
-    cookie_error_patterns.iter().any(|pattern| error_lower.contains(&pattern.to_lowercase()))
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| error_lower.contains(&pattern.to_lowercase()));
+    if matches(&rate_limit_patterns) {
+        ErrorCategory::RateLimited
+    } else if matches(&members_only_patterns) {
+        ErrorCategory::MembersOnly
+    } else if matches(&cookie_error_patterns) {
+        ErrorCategory::CookiesExpired
+    } else {
+        ErrorCategory::Other
+    }
+}
+
+// Classify a yt-dlp error message so the UI can react to it appropriately
+#[tauri::command]
+pub fn classify_yt_dlp_error(error: String) -> ErrorCategory {
+    classify_error(&error)
 }
 
 // Get video info with auto cookie refresh on auth errors
 #[tauri::command]
-pub async fn get_video_info_with_refresh(url: String) -> Result<CombinedVideoInfo, String> {
+pub async fn get_video_info_with_refresh(url: String, app: AppHandle) -> Result<CombinedVideoInfo, String> {
     // First attempt
-    match get_video_info_combined(url.clone()).await {
+    match get_video_info_combined(url.clone(), app.clone()).await {
         Ok(info) => Ok(info),
-        Err(e) if is_cookie_error(&e) => {
+        Err(e) if classify_error(&e) == ErrorCategory::MembersOnly => {
+            Err(format!("{}: This video requires channel membership", MEMBERS_ONLY_ERROR))
+        }
+        Err(e) if classify_error(&e) == ErrorCategory::CookiesExpired => {
             eprintln!("Cookie error detected, attempting to refresh cookies...");
             
             // Try to refresh cookies
             if let Err(refresh_err) = refresh_cookies(None).await {
-                return Err(format!("Original error: {}. Cookie refresh also failed: {}", e, refresh_err));
+                return Err(format!("{}: {}. Cookie refresh also failed: {}", COOKIES_EXPIRED_ERROR, e, refresh_err));
             }
             
             // Retry the request
-            get_video_info_combined(url).await
+            get_video_info_combined(url, app).await
                 .map_err(|retry_err| format!("Failed after cookie refresh: {}", retry_err))
         }
         Err(e) => Err(e),
     }
 }
+
+// Merge a video-only file and an audio-only file into one output without re-encoding
+#[tauri::command]
+pub async fn merge_av(
+    video_path: String,
+    audio_path: String,
+    output_path: String,
+    app: AppHandle,
+) -> Result<String, String> {
+    if find_ffmpeg().is_none() {
+        return Err("ffmpeg is required to merge video and audio".to_string());
+    }
+    for path in [&video_path, &audio_path] {
+        if !std::path::Path::new(path).exists() {
+            return Err(format!("{}: {}", FILE_NOT_FOUND_ERROR, path));
+        }
+    }
+    
+    // ffprobe and the mux run for as long as the files are big, so keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        // The video file must have a video stream and no audio of its own
+        let video_streams = get_stream_types(&video_path)?;
+        if !video_streams.iter().any(|t| t == "video") {
+            return Err(format!("{} does not contain a video stream", video_path));
+        }
+        if video_streams.iter().any(|t| t == "audio") {
+            return Err(format!("{} already contains an audio stream", video_path));
+        }
+        
+        // The audio file must be audio-only
+        let audio_streams = get_stream_types(&audio_path)?;
+        if !audio_streams.iter().any(|t| t == "audio") {
+            return Err(format!("{} does not contain an audio stream", audio_path));
+        }
+        if audio_streams.iter().any(|t| t == "video") {
+            return Err(format!("{} is not an audio-only file", audio_path));
+        }
+        
+        // Warn when the tracks are clearly from different sources or cut differently
+        let video_duration = get_media_duration(&video_path).unwrap_or(0.0);
+        let audio_duration = get_media_duration(&audio_path).unwrap_or(0.0);
+        let difference = (video_duration - audio_duration).abs();
+        if video_duration > 0.0 && audio_duration > 0.0 && difference > 2.0 {
+            let emit_result = app.emit("merge-warning", serde_json::json!({
+                "id": output_path,
+                "message": format!(
+                    "Video ({:.1}s) and audio ({:.1}s) durations differ by {:.1}s",
+                    video_duration, audio_duration, difference
+                )
+            }));
+            if let Err(e) = emit_result {
+                eprintln!("Failed to emit merge-warning: {}", e);
+            }
+        }
+        
+        let args = vec![
+            "-i".to_string(), video_path,
+            "-i".to_string(), audio_path,
+            "-map".to_string(), "0:v:0".to_string(),
+            "-map".to_string(), "1:a:0".to_string(),
+            "-c".to_string(), "copy".to_string(),
+            output_path.clone(),
+        ];
+        run_ffmpeg_with_progress(&app, "merge-progress", &output_path, &args, video_duration.max(audio_duration))?;
+        
+        Ok(output_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to merge video and audio: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_url_list_takes_first_link_per_line() {
+        let content = "\
+url,title
+# saved for later
+\"https://www.youtube.com/watch?v=dQw4w9WgXcQ\",Never Gonna Give You Up
+
+notes about youtube;https://youtu.be/abc123
+youtu.be/xyz789\tno scheme
+https://www.youtube.com/watch?v=dQw4w9WgXcQ
+";
+        assert_eq!(parse_url_list(content), vec![
+            (3, "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()),
+            (5, "https://youtu.be/abc123".to_string()),
+            (6, "youtu.be/xyz789".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn parse_url_list_skips_text_mentioning_youtube() {
+        assert!(parse_url_list("my youtube favourites\nyoutube,channel,list").is_empty());
+    }
+
+    #[test]
+    fn validate_output_template_fields_accepts_yt_dlp_extras() {
+        assert!(validate_output_template_fields("%(title)s.%(ext)s").is_ok());
+        assert!(validate_output_template_fields("%(upload_date>%Y)s/%(artist,uploader|Unknown)s 100%%.%(ext)s").is_ok());
+    }
+
+    #[test]
+    fn validate_output_template_fields_rejects_bad_fields() {
+        assert_eq!(
+            validate_output_template_fields("%(title)s.mp4"),
+            Err("Output template must include %(ext)s".to_string())
+        );
+        assert_eq!(
+            validate_output_template_fields("%(not_a_field)s.%(ext)s"),
+            Err("Unknown output template field: not_a_field".to_string())
+        );
+        assert!(validate_output_template_fields("%()s.%(ext)s").is_err());
+    }
+
+    #[test]
+    fn is_transient_download_error_matches_network_and_throttling() {
+        assert!(is_transient_download_error("ERROR: unable to download video data: HTTP Error 403: Forbidden"));
+        assert!(is_transient_download_error("ERROR: [youtube] abc: TOO MANY REQUESTS"));
+        assert!(is_transient_download_error("urlopen error [Errno 104] Connection reset by peer"));
+        assert!(!is_transient_download_error("ERROR: [youtube] abc: Video unavailable"));
+        assert!(!is_transient_download_error("ERROR: [youtube] abc: Sign in to confirm your age"));
+    }
+
+    #[test]
+    fn output_template_with_suffix_goes_before_extension() {
+        assert_eq!(output_template_with_suffix("/videos/%(title)s.%(ext)s", " (1)"), "/videos/%(title)s (1).%(ext)s");
+        assert_eq!(output_template_with_suffix("/videos/clip.mp4", "-2"), "/videos/clip-2.mp4");
+        assert_eq!(output_template_with_suffix("/videos/clip", "-2"), "/videos/clip-2");
+    }
+
+    #[test]
+    fn select_translated_subtitle_prefers_original_tracks() {
+        let json = serde_json::json!({
+            "subtitles": { "en": [{ "ext": "vtt" }] },
+            "automatic_captions": {
+                "de-orig": [{ "ext": "vtt" }],
+                "de": [{ "ext": "vtt" }],
+                "fr": [{ "ext": "vtt" }],
+                "es": []
+            }
+        });
+        assert_eq!(select_translated_subtitle(&json, "en"), Some(("en".to_string(), SubtitleSource::Original)));
+        assert_eq!(select_translated_subtitle(&json, "de"), Some(("de-orig".to_string(), SubtitleSource::Original)));
+        assert_eq!(select_translated_subtitle(&json, "fr"), Some(("fr".to_string(), SubtitleSource::Translated)));
+        assert_eq!(select_translated_subtitle(&json, "es"), None);
+    }
+
+    #[test]
+    fn classify_url_structure_recognizes_youtube_urls() {
+        assert_eq!(classify_url_structure("https://www.youtube.com/shorts/abc123"), Some(UrlKind::Short));
+        assert_eq!(classify_url_structure("https://youtube.com/live/abc123"), Some(UrlKind::Live));
+        assert_eq!(classify_url_structure("https://www.youtube.com/playlist?list=PL123"), Some(UrlKind::Playlist));
+        assert_eq!(classify_url_structure("https://www.youtube.com/@someone/videos"), Some(UrlKind::Channel));
+        assert_eq!(classify_url_structure("https://m.youtube.com/channel/UC123"), Some(UrlKind::Channel));
+        assert_eq!(classify_url_structure("https://www.youtube.com/@someone/live"), Some(UrlKind::Live));
+        assert_eq!(classify_url_structure("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), None);
+        assert_eq!(classify_url_structure("https://example.com/@someone"), None);
+    }
+
+    #[test]
+    fn parse_progress_template_uses_bytes_then_fragments() {
+        let update = parse_progress_template(
+            r#"[progress] {"status": "downloading", "downloaded_bytes": 5242880, "total_bytes": null, "total_bytes_estimate": 10485760.0, "speed": 1048576.0, "eta": 65}"#,
+        ).unwrap();
+        assert_eq!(update.progress, 50.0);
+        assert_eq!(update.speed, "1.00MiB/s");
+        assert_eq!(update.eta, "01:05");
+        assert_eq!(update.total_bytes, Some(10485760));
+        
+        let update = parse_progress_template(
+            r#"[progress] {"downloaded_bytes": 1000, "fragment_index": 3, "fragment_count": 12}"#,
+        ).unwrap();
+        assert_eq!(update.progress, 25.0);
+        assert_eq!(update.fragment_count, Some(12));
+        assert_eq!(update.speed, "");
+    }
+
+    #[test]
+    fn parse_progress_template_ignores_other_lines() {
+        assert!(parse_progress_template("[download]  50.0% of 10.00MiB at 1.00MiB/s ETA 00:05").is_none());
+        assert!(parse_progress_template(r#"[progress] {"downloaded_bytes": 1000}"#).is_none());
+        assert!(parse_progress_template("[progress] not json").is_none());
+    }
+
+    #[test]
+    fn info_fetch_limiter_bounds_and_spaces_fetches() {
+        let settings = AppSettings {
+            max_concurrent_info_fetches: 2,
+            info_fetch_interval_ms: 100,
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        let first = acquire_info_fetch_slot(&settings);
+        let second = acquire_info_fetch_slot(&settings);
+        // The second fetch waits out the spacing after the first
+        assert!(started.elapsed() >= std::time::Duration::from_millis(100));
+        
+        // A third waits for a running fetch to finish
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let waiting_settings = settings.clone();
+        let waiter = std::thread::spawn(move || {
+            let permit = acquire_info_fetch_slot(&waiting_settings);
+            sender.send(()).unwrap();
+            drop(permit);
+        });
+        assert!(receiver.recv_timeout(std::time::Duration::from_millis(300)).is_err());
+        
+        drop(first);
+        assert!(receiver.recv_timeout(std::time::Duration::from_secs(2)).is_ok());
+        waiter.join().unwrap();
+        drop(second);
+    }
+}
@@ -4,24 +4,77 @@
 mod commands;
 
 use commands::DownloadManager;
+use tauri::{Manager, RunEvent};
 
 fn main() {
     let download_manager = DownloadManager::new();
 
     tauri::Builder::default()
         .manage(download_manager)
+        .setup(|app| {
+            commands::init_settings();
+            commands::remove_stale_temp_cookies();
+            commands::start_cookie_refresh_timer(app.handle().clone());
+            commands::start_download_scheduler(app.handle().clone());
+            commands::start_subscription_checker(app.handle().clone());
+            commands::start_clipboard_monitor(app.handle().clone());
+            commands::restore_pending_downloads(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::validate_url,
+            commands::classify_url,
+            commands::get_channel_info,
+            commands::download_channel_entries,
+            commands::import_urls_from_file,
+            commands::add_subscription,
+            commands::get_subscriptions,
+            commands::remove_subscription,
+            commands::check_subscriptions_now,
+            commands::check_dependencies,
+            commands::check_yt_dlp_freshness,
+            commands::check_js_runtime,
             commands::get_video_info,
+            commands::get_video_info_batch,
             commands::get_video_info_combined,
             commands::get_video_info_with_refresh,
+            commands::classify_yt_dlp_error,
             commands::get_available_formats,
+            commands::get_formats_grouped,
+            commands::get_storyboard,
+            commands::get_thumbnails,
+            commands::fetch_thumbnail_base64,
+            commands::check_format_compatibility,
+            commands::find_working_client,
             commands::get_available_subtitles,
+            commands::get_raw_video_json,
             commands::start_download,
+            commands::download_specific_format,
+            commands::download_chapter,
+            commands::enqueue_download,
+            commands::get_queue,
+            commands::retry_download,
+            commands::set_max_concurrent,
+            commands::schedule_download,
+            commands::get_scheduled_downloads,
+            commands::cancel_scheduled_download,
             commands::cancel_download,
+            commands::save_pending_queue,
+            commands::restore_queue,
+            commands::prepare_shutdown,
+            commands::pause_download,
+            commands::resume_download,
+            commands::pause_all_downloads,
+            commands::resume_all_downloads,
+            commands::start_watch_list,
+            commands::stop_watch_list,
             commands::get_download_progress,
+            commands::list_active_downloads,
+            commands::get_download_log,
+            commands::open_log_folder,
             commands::save_credentials,
             commands::load_credentials,
+            commands::migrate_credentials,
             commands::clear_credentials,
             commands::select_save_location,
             commands::get_default_save_location,
@@ -30,14 +83,65 @@ fn main() {
             commands::save_recent_download,
             commands::open_file,
             commands::open_file_with,
+            commands::open_with_player,
+            commands::save_playback_position,
+            commands::get_playback_position,
             commands::get_apps_for_file,
             commands::open_in_folder,
+            commands::open_download_in_folder,
             commands::delete_file,
             commands::clear_recent_downloads,
             commands::remove_recent_download,
+            commands::delete_downloads,
+            commands::rename_history_entry,
+            commands::rename_download_file,
+            commands::refresh_history_entry,
+            commands::refresh_all_history,
+            commands::generate_playlist_file,
+            commands::export_nfo,
+            commands::find_duplicate_downloads,
+            commands::generate_checksums,
+            commands::verify_checksums,
+            commands::get_download_stats,
             commands::get_file_size,
+            commands::probe_file,
+            commands::transcode_video,
+            commands::merge_av,
+            commands::recover_partial_downloads,
+            commands::resume_partial_download,
             commands::refresh_cookies,
+            commands::validate_cookies_file,
+            commands::get_settings,
+            commands::save_settings,
+            commands::get_system_locale,
+            commands::get_default_quality,
+            commands::set_default_quality,
+            commands::get_default_subtitle_langs,
+            commands::set_default_subtitle_langs,
+            commands::get_common_subtitle_languages,
+            commands::save_format_preference,
+            commands::load_format_preference,
+            commands::get_output_template,
+            commands::set_output_template,
+            commands::preview_output_name,
+            commands::reset_output_template,
+            commands::set_max_history,
+            commands::set_global_rate_limit,
+            commands::set_speed_limit,
+            commands::set_extractor_args,
+            commands::set_http_headers,
+            commands::login_oauth,
+            commands::get_playlist_progress,
+            commands::get_playlist_size_estimate,
+            commands::get_playlist_range_count,
+            commands::reset_playlist_archive,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Don't leave yt-dlp running as an orphan after the app quits
+            if let RunEvent::ExitRequested { .. } = event {
+                commands::shutdown_downloads(&app.state::<DownloadManager>());
+            }
+        });
 }
@@ -1,4 +1,4 @@
-import { useEffect, useRef } from 'react';
+import { useEffect, useRef, useState } from 'react';
 import { useAppStore } from './stores/app-store';
 import { URLInput } from './components/URLInput';
 import { QualitySelector } from './components/QualitySelector';
@@ -7,8 +7,25 @@ import { SaveLocationPicker } from './components/SaveLocationPicker';
 import { RecentDownloads } from './components/RecentDownloads';
 import { generateOutputPath } from './utils/formatting';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import './App.css';
 
+// Payload of the backend's signature-extraction-warning event
+interface SignatureWarningEvent {
+  id: string | null;
+  warning: {
+    message: string;
+    yt_dlp_version: string | null;
+    suggestions: string[];
+  };
+  update: {
+    status: 'UpToDate' | 'UpdateAvailable' | 'TooOld';
+    installed: string;
+    recommended: string;
+    latest: string | null;
+  } | null;
+}
+
 function App() {
   const {
     videoInfo,
@@ -23,6 +40,7 @@ function App() {
   } = useAppStore();
 
   const downloadButtonRef = useRef<HTMLButtonElement>(null);
+  const [signatureWarning, setSignatureWarning] = useState<SignatureWarningEvent | null>(null);
 
   useEffect(() => {
     // Initialize save location with default (no dialog)
@@ -30,6 +48,16 @@ function App() {
     loadRecentDownloads();
   }, [loadRecentDownloads]);
 
+  // Signature extraction failures are usually fixed by updating yt-dlp, so prompt for it
+  useEffect(() => {
+    const unlisten = listen<SignatureWarningEvent>('signature-extraction-warning', (event) => {
+      setSignatureWarning(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Auto-scroll to download button when video info is loaded
   useEffect(() => {
     if (videoInfo && downloadButtonRef.current) {
@@ -116,6 +144,27 @@ function App() {
             Download
           </button>
 
+          {signatureWarning && (
+            <div className="update-prompt">
+              <div>
+                {signatureWarning.update && signatureWarning.update.status !== 'UpToDate' ? (
+                  <strong>
+                    yt-dlp {signatureWarning.update.installed} couldn't read YouTube's signatures.
+                    Update to {signatureWarning.update.latest || signatureWarning.update.recommended} or later.
+                  </strong>
+                ) : (
+                  <strong>yt-dlp couldn't read YouTube's signatures; some formats may be missing.</strong>
+                )}
+                <ul>
+                  {signatureWarning.warning.suggestions.map((suggestion) => (
+                    <li key={suggestion}>{suggestion}</li>
+                  ))}
+                </ul>
+              </div>
+              <button onClick={() => setSignatureWarning(null)} className="close-error">×</button>
+            </div>
+          )}
+
           {error && (
             <div className="error-message">
               {error}
@@ -179,13 +179,30 @@ export const useAppStore = create<AppStore>((set, get) => ({
       });
 
       // Listen for download complete event
-      const unlisten = await listen('download-complete', async (event) => {
+      const unlisten = await listen<{ id: string; path?: string; files: string[]; chapters?: string[]; error?: string }>('download-complete', async (event) => {
         console.log('Download complete event:', event.payload);
-        if (event.payload === downloadId) {
+        if (event.payload.id === downloadId && event.payload.error) {
+          // e.g. "FileTooLarge: ..." when the video exceeds the max file size
+          set((state) => ({
+            downloadQueue: state.downloadQueue.map((d) =>
+              d.id === id ? { ...d, status: 'failed' as const, error: event.payload.error } : d
+            ),
+          }));
+          unlistenProgress();
+          unlisten();
+        } else if (event.payload.id === downloadId) {
+          // Use the file yt-dlp actually wrote, which can differ from the template
+          const filePath = event.payload.path || item.outputPath;
+          const chapters = event.payload.chapters || [];
+          // Other files from this download (e.g. extracted audio); chapters get their own entries
+          const extraFiles = (event.payload.files || []).filter(
+            (file) => file !== filePath && !chapters.includes(file)
+          );
+
           // Get file size
           let fileSize = 0;
           try {
-            fileSize = await invoke<number>('get_file_size', { path: item.outputPath });
+            fileSize = await invoke<number>('get_file_size', { path: filePath });
           } catch (err) {
             console.error('Failed to get file size:', err);
           }
@@ -217,18 +234,48 @@ export const useAppStore = create<AppStore>((set, get) => ({
               id: item.id,
               title: item.title,
               url: item.url,
-              filePath: item.outputPath,
+              filePath,
               thumbnail: videoInfo?.thumbnail || '',
               size: fileSize,
               duration: videoInfo?.duration || 0,
+              uploader: videoInfo?.uploader || '',
               quality: item.quality,
               downloadedAt: completedAt,
               format: item.format,
+              extraFiles,
             });
           } catch (err) {
             console.error('Failed to save recent download:', err);
           }
 
+          // Each file split off by chapter gets its own entry
+          for (const [index, chapterPath] of chapters.entries()) {
+            const chapterTitle = chapterPath.split(/[\\/]/).pop()?.replace(/\.[^.]+$/, '') || item.title;
+            let chapterSize = 0;
+            try {
+              chapterSize = await invoke<number>('get_file_size', { path: chapterPath });
+            } catch (err) {
+              console.error('Failed to get file size:', err);
+            }
+            try {
+              await get().addRecentDownload({
+                id: `${item.id}-chapter-${index + 1}`,
+                title: chapterTitle,
+                url: item.url,
+                filePath: chapterPath,
+                thumbnail: videoInfo?.thumbnail || '',
+                size: chapterSize,
+                duration: 0,
+                uploader: videoInfo?.uploader || '',
+                quality: item.quality,
+                downloadedAt: completedAt,
+                format: item.format,
+              });
+            } catch (err) {
+              console.error('Failed to save recent download:', err);
+            }
+          }
+
           unlistenProgress();
           unlisten();
         }
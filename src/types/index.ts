@@ -12,6 +12,7 @@ export interface VideoInfo {
   duration: number;
   uploader: string;
   thumbnail: string;
+  channel_id?: string;
 }
 
 export interface VideoFormat {
@@ -59,6 +60,20 @@ export interface RecentDownload {
   quality: string;
   downloadedAt: string;
   format: string;
+  extraFiles?: string[];
+  uploader?: string;
+  probe?: MediaProbe;
+}
+
+export interface MediaProbe {
+  container: string;
+  video_codec?: string;
+  audio_codec?: string;
+  width?: number;
+  height?: number;
+  fps?: number;
+  duration?: number;
+  bitrate?: number;
 }
 
 export interface Credentials {